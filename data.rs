@@ -0,0 +1,251 @@
+use crate::errors::PSP22Error;
+use ink::prelude::{vec, vec::Vec};
+use ink::primitives::AccountId;
+use ink::storage::Mapping;
+
+/// Event produced by a `PSP22Data` operation, to be emitted by the embedding contract.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PSP22Event {
+    Transfer {
+        from: Option<AccountId>,
+        to: Option<AccountId>,
+        value: u128,
+    },
+    Approval {
+        owner: AccountId,
+        spender: AccountId,
+        value: u128,
+    },
+}
+
+/// Balance and allowance bookkeeping of a PSP22 token.
+///
+/// Embed it in the storage of an ink! contract, call its methods from the contract's messages
+/// and emit the returned events as contract events.
+#[ink::storage_item]
+#[derive(Default)]
+pub struct PSP22Data {
+    total_supply: u128,
+
+    balances: Mapping<AccountId, u128>,
+
+    allowances: Mapping<(AccountId, AccountId), u128>,
+}
+
+impl PSP22Data {
+    /// Creates a token with `supply` tokens credited to `creator`.
+    pub fn new(supply: u128, creator: AccountId) -> (PSP22Data, Vec<PSP22Event>) {
+        let mut data = PSP22Data {
+            total_supply: supply,
+            balances: Default::default(),
+            allowances: Default::default(),
+        };
+        data.balances.insert(creator, &supply);
+        let events = vec![PSP22Event::Transfer {
+            from: None,
+            to: Some(creator),
+            value: supply,
+        }];
+        (data, events)
+    }
+
+    pub fn total_supply(&self) -> u128 {
+        self.total_supply
+    }
+
+    pub fn balance_of(&self, owner: AccountId) -> u128 {
+        self.balances.get(owner).unwrap_or_default()
+    }
+
+    pub fn allowance(&self, owner: AccountId, spender: AccountId) -> u128 {
+        self.allowances.get((owner, spender)).unwrap_or_default()
+    }
+
+    /// Moves `value` tokens from `caller` to `to`.
+    pub fn transfer(&mut self, caller: AccountId, to: AccountId, value: u128) -> Result<Vec<PSP22Event>, PSP22Error> {
+        //Reverts with error `InsufficientBalance` if there are not enough tokens on the caller's account.
+        if self.balance_of(caller) < value {
+            return Err(PSP22Error::InsufficientBalance);
+        }
+        //Reverts with error `ZeroSenderAddress` if sender's address is zero.
+        if caller == AccountId::from([0x0; 32]) {
+            return Err(PSP22Error::ZeroSenderAddress);
+        }
+        //Reverts with error `ZeroRecipientAddress` if recipient's address is zero.
+        if to == AccountId::from([0x0; 32]) {
+            return Err(PSP22Error::ZeroRecipientAddress);
+        }
+        self.move_balance(caller, to, value)?;
+        Ok(vec![PSP22Event::Transfer {
+            from: Some(caller),
+            to: Some(to),
+            value,
+        }])
+    }
+
+    /// Moves `value` tokens from `from` to `to` on behalf of `caller`, spending `caller`'s allowance.
+    pub fn transfer_from(
+        &mut self,
+        caller: AccountId,
+        from: AccountId,
+        to: AccountId,
+        value: u128,
+    ) -> Result<Vec<PSP22Event>, PSP22Error> {
+        //Reverts with error `InsufficientAllowance` if there are not enough tokens allowed for the caller's account.
+        let allowance = self.allowance(from, caller);
+        if allowance < value {
+            return Err(PSP22Error::InsufficientAllowance);
+        }
+        //Reverts with error `InsufficientBalance` if there are not enough tokens on the `from` account.
+        if self.balance_of(from) < value {
+            return Err(PSP22Error::InsufficientBalance);
+        }
+        //Reverts with error `ZeroSenderAddress` if sender's address is zero.
+        if from == AccountId::from([0x0; 32]) {
+            return Err(PSP22Error::ZeroSenderAddress);
+        }
+        //Reverts with error `ZeroRecipientAddress` if recipient's address is zero.
+        if to == AccountId::from([0x0; 32]) {
+            return Err(PSP22Error::ZeroRecipientAddress);
+        }
+        //Decreases the allowance by the transferred amount.
+        let allowance = allowance.checked_sub(value).ok_or(PSP22Error::InsufficientAllowance)?;
+        self.allowances.insert((from, caller), &allowance);
+        self.move_balance(from, to, value)?;
+        Ok(vec![PSP22Event::Transfer {
+            from: Some(from),
+            to: Some(to),
+            value,
+        }])
+    }
+
+    /// Sets `value` as the allowance of `spender` over `owner`'s tokens.
+    pub fn approve(&mut self, owner: AccountId, spender: AccountId, value: u128) -> Result<Vec<PSP22Event>, PSP22Error> {
+        self.allowances.insert((owner, spender), &value);
+        Ok(vec![PSP22Event::Approval { owner, spender, value }])
+    }
+
+    /// Increases the allowance of `spender` over `owner`'s tokens by `added_value`.
+    pub fn increase_allowance(
+        &mut self,
+        owner: AccountId,
+        spender: AccountId,
+        added_value: u128,
+    ) -> Result<Vec<PSP22Event>, PSP22Error> {
+        //Reverts with error `ZeroSenderAddress` if sender's address is zero.
+        if owner == AccountId::from([0x0; 32]) {
+            return Err(PSP22Error::ZeroSenderAddress);
+        }
+        //Reverts with error `ZeroRecipientAddress` if recipient's address is zero.
+        if spender == AccountId::from([0x0; 32]) {
+            return Err(PSP22Error::ZeroRecipientAddress);
+        }
+        //Reverts with error `Overflow` if the new allowance does not fit into `u128`.
+        let allowance = self
+            .allowance(owner, spender)
+            .checked_add(added_value)
+            .ok_or(PSP22Error::Overflow)?;
+        self.allowances.insert((owner, spender), &allowance);
+        Ok(vec![PSP22Event::Approval {
+            owner,
+            spender,
+            value: allowance,
+        }])
+    }
+
+    /// Decreases the allowance of `spender` over `owner`'s tokens by `subtracted_value`.
+    pub fn decrease_allowance(
+        &mut self,
+        owner: AccountId,
+        spender: AccountId,
+        subtracted_value: u128,
+    ) -> Result<Vec<PSP22Event>, PSP22Error> {
+        //Reverts with error `InsufficientAllowance` if there are not enough tokens allowed for the spender.
+        let allowance = self.allowance(owner, spender);
+        if allowance < subtracted_value {
+            return Err(PSP22Error::InsufficientAllowance);
+        }
+        //Reverts with error `ZeroSenderAddress` if sender's address is zero.
+        if owner == AccountId::from([0x0; 32]) {
+            return Err(PSP22Error::ZeroSenderAddress);
+        }
+        //Reverts with error `ZeroRecipientAddress` if recipient's address is zero.
+        if spender == AccountId::from([0x0; 32]) {
+            return Err(PSP22Error::ZeroRecipientAddress);
+        }
+        let allowance = allowance
+            .checked_sub(subtracted_value)
+            .ok_or(PSP22Error::InsufficientAllowance)?;
+        self.allowances.insert((owner, spender), &allowance);
+        Ok(vec![PSP22Event::Approval {
+            owner,
+            spender,
+            value: allowance,
+        }])
+    }
+
+    /// Creates `value` new tokens and credits them to `to`.
+    pub fn mint(&mut self, to: AccountId, value: u128) -> Result<Vec<PSP22Event>, PSP22Error> {
+        //Reverts with error `ZeroRecipientAddress` if recipient's address is zero.
+        if to == AccountId::from([0x0; 32]) {
+            return Err(PSP22Error::ZeroRecipientAddress);
+        }
+        //Reverts with error `Overflow` if the new total supply does not fit into `u128`.
+        let total_supply = self.total_supply.checked_add(value).ok_or(PSP22Error::Overflow)?;
+        //Reverts with error `Overflow` if the balance of `to` does not fit into `u128`.
+        let to_balance = self.balance_of(to).checked_add(value).ok_or(PSP22Error::Overflow)?;
+        self.total_supply = total_supply;
+        self.balances.insert(to, &to_balance);
+        Ok(vec![PSP22Event::Transfer {
+            from: None,
+            to: Some(to),
+            value,
+        }])
+    }
+
+    /// Destroys `value` tokens held by `from`.
+    pub fn burn(&mut self, from: AccountId, value: u128) -> Result<Vec<PSP22Event>, PSP22Error> {
+        //Reverts with error `InsufficientBalance` if there are not enough tokens on the `from` account.
+        let balance = self
+            .balance_of(from)
+            .checked_sub(value)
+            .ok_or(PSP22Error::InsufficientBalance)?;
+        let total_supply = self.total_supply.checked_sub(value).ok_or(PSP22Error::Overflow)?;
+        self.balances.insert(from, &balance);
+        self.total_supply = total_supply;
+        Ok(vec![PSP22Event::Transfer {
+            from: Some(from),
+            to: None,
+            value,
+        }])
+    }
+
+    /// Destroys `value` tokens held by `from` on behalf of `caller`, spending `caller`'s allowance.
+    pub fn burn_from(&mut self, caller: AccountId, from: AccountId, value: u128) -> Result<Vec<PSP22Event>, PSP22Error> {
+        //Reverts with error `InsufficientAllowance` if there are not enough tokens allowed for the caller's account.
+        let allowance = self
+            .allowance(from, caller)
+            .checked_sub(value)
+            .ok_or(PSP22Error::InsufficientAllowance)?;
+        let events = self.burn(from, value)?;
+        self.allowances.insert((from, caller), &allowance);
+        Ok(events)
+    }
+
+    //Decreases the balance of `from` and increases the balance of `to` by the same amount.
+    //Both balances are computed before either is written, so an error leaves the data unchanged.
+    fn move_balance(&mut self, from: AccountId, to: AccountId, value: u128) -> Result<(), PSP22Error> {
+        let from_balance = self
+            .balance_of(from)
+            .checked_sub(value)
+            .ok_or(PSP22Error::InsufficientBalance)?;
+        if from == to {
+            return Ok(());
+        }
+        //Reverts with error `Overflow` if the balance of `to` does not fit into `u128`.
+        let to_balance = self.balance_of(to).checked_add(value).ok_or(PSP22Error::Overflow)?;
+        self.balances.insert(from, &from_balance);
+        self.balances.insert(to, &to_balance);
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use ink::prelude::boxed::Box;
+use ink::prelude::string::String;
+use ink::primitives::AccountId;
+
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum PSP22Error {
+    /// Custom error type for cases in which an implementation adds its own restrictions.
+    Custom(String),
+    /// Returned if not enough balance to fulfill a request is available.
+    InsufficientBalance,
+    /// Returned if not enough allowance to fulfill a request is available.
+    InsufficientAllowance,
+    /// Returned if recipient's address is zero.
+    ZeroRecipientAddress,
+    /// Returned if sender's address is zero.
+    ZeroSenderAddress,
+    /// Returned if a safe transfer check fails (e.g. if the receiving contract does not accept tokens).
+    SafeTransferCheckFailed(String),
+    /// Returned if an arithmetic operation on a balance, an allowance or the total supply overflows.
+    Overflow,
+    /// Returned if the contract is paused.
+    Paused,
+    /// Returned if the contract is not paused.
+    NotPaused,
+    /// Returned if a permit is submitted after its deadline.
+    PermitExpired,
+    /// Returned if a permit signature was not made by the owner over the expected payload.
+    PermitInvalidSignature,
+    /// Returned if the sender or the recipient of a transfer is blacklisted.
+    Blacklisted(AccountId),
+    /// Returned if the contract is in allowlist-only mode and the sender or the recipient is not allowlisted.
+    NotAllowlisted(AccountId),
+    /// Returned if an operation would raise the total supply above the cap.
+    CapExceeded,
+    /// Returned if the receiver of a flash loan rejects it.
+    FlashLoanRejected(String),
+    /// Returned if the receiver of a flash loan does not pay back the loan and the fee.
+    FlashLoanRepaymentFailed,
+    /// Returned if a vesting schedule has a zero duration or a cliff longer than its duration.
+    InvalidVestingSchedule,
+    /// Returned if the beneficiary already has a vesting schedule.
+    VestingScheduleExists,
+    /// Returned if enough tokens are held but part of them is still time-locked.
+    BalanceLocked,
+    /// Returned if the transfer to the recipient at the given index of a batch fails.
+    BatchTransferFailed(u32, Box<PSP22Error>),
+    /// Returned if an airdrop claim was already made.
+    AirdropAlreadyClaimed,
+    /// Returned if an airdrop claim is not proven to be part of the Merkle root.
+    InvalidMerkleProof,
+    /// Returned if a flash loan borrower calls back a message that is not available during the loan.
+    FlashLoanInProgress,
+    /// Returned if a time lock of zero tokens is requested.
+    ZeroLockValue,
+    /// Returned if the recipient already has the maximum number of active time locks.
+    TooManyLocks,
+    /// Returned if tokens would be time-locked for longer than the maximum lock duration.
+    LockTooLong,
+}
+
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum PSP22ReceiverError {
+    /// Returned if the receiving contract does not accept the tokens.
+    TransferRejected(String),
+}
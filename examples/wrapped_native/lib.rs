@@ -0,0 +1,254 @@
+//! PSP22 token wrapping the native currency, built on `psp22_ink::PSP22Data`.
+//!
+//! This crate is kept out of a workspace with `psp22_ink`: building them together would unify features and
+//! compile `psp22_ink` itself with `ink-as-dependency`. Run its tests from the repository root with
+//! `cargo test --manifest-path examples/wrapped_native/Cargo.toml`.
+
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+#[ink::contract]
+mod wrapped_native {
+    use ink::prelude::string::{String, ToString};
+    use ink::prelude::vec::Vec;
+    use psp22_ink::{check_receiver, PSP22Data, PSP22Error, PSP22Event, PSP22Metadata, PSP22};
+
+    /// PSP22 token backed 1:1 by the native currency locked in the contract.
+    ///
+    /// Tokens only come into existence through `deposit` and leave through `withdraw`,
+    /// so the total supply always equals the native value deposited and not yet withdrawn.
+    #[ink(storage)]
+    #[derive(Default)]
+    pub struct WrappedNative {
+        data: PSP22Data,
+
+        name: Option<String>,
+
+        symbol: Option<String>,
+
+        decimals: u8,
+    }
+
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Approval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        value: Balance,
+    }
+
+    pub type Result<T> = core::result::Result<T, PSP22Error>;
+
+    impl WrappedNative {
+
+        #[ink(constructor)]
+        pub fn new(name: Option<String>, symbol: Option<String>, decimals: u8) -> Self {
+            Self {
+                data: PSP22Data::default(),
+                name,
+                symbol,
+                decimals,
+            }
+        }
+
+        /// Wraps the transferred native value into the same amount of tokens credited to the caller.
+        #[ink(message, payable)]
+        pub fn deposit(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            let events = self.data.mint(caller, self.env().transferred_value())?;
+            self.emit_events(events);
+            Ok(())
+        }
+
+        /// Burns `value` tokens of the caller and sends the same amount of native value back to them.
+        #[ink(message)]
+        pub fn withdraw(&mut self, value: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            let events = self.data.burn(caller, value)?;
+            //Reverts with error `Custom` if the native value cannot be sent back to the caller.
+            self.env()
+                .transfer(caller, value)
+                .map_err(|_| PSP22Error::Custom("Native transfer failed".to_string()))?;
+            self.emit_events(events);
+            Ok(())
+        }
+
+        //Emits the contract events corresponding to events returned by `PSP22Data`.
+        fn emit_events(&self, events: Vec<PSP22Event>) {
+            for event in events {
+                match event {
+                    PSP22Event::Transfer { from, to, value } => {
+                        self.env().emit_event(Transfer { from, to, value })
+                    }
+                    PSP22Event::Approval { owner, spender, value } => {
+                        self.env().emit_event(Approval { owner, spender, value })
+                    }
+                }
+            }
+        }
+    }
+
+    impl PSP22 for WrappedNative{
+        #[ink(message)]
+        fn total_supply(&self) -> Balance {
+            self.data.total_supply()
+        }
+
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> Balance {
+            self.data.balance_of(owner)
+        }
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            self.data.allowance(owner, spender)
+        }
+
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, value: Balance, data: Vec<u8>) -> Result<()> {
+            let from = self.env().caller();
+            let events = self.data.transfer(from, to, value)?;
+            //Reverts with error `SafeTransferCheckFailed` if the recipient is a contract and rejected the transfer.
+            check_receiver(from, from, to, value, data)?;
+            self.emit_events(events);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance, data: Vec<u8>) -> Result<()> {
+            let caller = self.env().caller();
+            let events = self.data.transfer_from(caller, from, to, value)?;
+            //Reverts with error `SafeTransferCheckFailed` if the recipient is a contract and rejected the transfer.
+            check_receiver(caller, from, to, value, data)?;
+            self.emit_events(events);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            let events = self.data.approve(owner, spender, value)?;
+            self.emit_events(events);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn increase_allowance(&mut self, spender: AccountId, added_value: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            let events = self.data.increase_allowance(owner, spender, added_value)?;
+            self.emit_events(events);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn decrease_allowance(&mut self, spender: AccountId, subtracted_value: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            let events = self.data.decrease_allowance(owner, spender, subtracted_value)?;
+            self.emit_events(events);
+            Ok(())
+        }
+    }
+
+    impl PSP22Metadata for WrappedNative{
+        #[ink(message)]
+        fn token_name(&self) -> Option<String> {
+            self.name.clone()
+        }
+
+        #[ink(message)]
+        fn token_symbol(&self) -> Option<String> {
+            self.symbol.clone()
+        }
+
+        #[ink(message)]
+        fn token_decimals(&self) -> u8 {
+            self.decimals
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn default_accounts() -> ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment> {
+            ink::env::test::default_accounts::<ink::env::DefaultEnvironment>()
+        }
+
+        fn contract_id() -> AccountId {
+            ink::env::test::callee::<ink::env::DefaultEnvironment>()
+        }
+
+        fn native_balance_of(account: AccountId) -> Balance {
+            ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account)
+                .expect("account has no native balance")
+        }
+
+        #[ink::test]
+        fn new_has_no_supply() {
+            let wrapped = WrappedNative::new(Some("Wrapped Native".to_string()), Some("WNAT".to_string()), 12);
+
+            assert_eq!(wrapped.total_supply(), 0);
+            assert_eq!(wrapped.token_symbol(), Some("WNAT".to_string()));
+            assert_eq!(wrapped.token_decimals(), 12);
+        }
+
+        #[ink::test]
+        fn deposit_mints_transferred_value() {
+            let accounts = default_accounts();
+            let mut wrapped = WrappedNative::new(None, None, 12);
+
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(wrapped.deposit(), Ok(()));
+            assert_eq!(wrapped.balance_of(accounts.alice), 100);
+            assert_eq!(wrapped.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn withdraw_burns_and_returns_native_value() {
+            let accounts = default_accounts();
+            let mut wrapped = WrappedNative::new(None, None, 12);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(wrapped.deposit(), Ok(()));
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(contract_id(), 100);
+            let alice_native_balance = native_balance_of(accounts.alice);
+
+            assert_eq!(wrapped.withdraw(40), Ok(()));
+            assert_eq!(wrapped.balance_of(accounts.alice), 60);
+            assert_eq!(wrapped.total_supply(), 60);
+            assert_eq!(native_balance_of(contract_id()), 60);
+            assert_eq!(native_balance_of(accounts.alice), alice_native_balance + 40);
+        }
+
+        #[ink::test]
+        fn transfer_to_account_works() {
+            let accounts = default_accounts();
+            let mut wrapped = WrappedNative::new(None, None, 12);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(wrapped.deposit(), Ok(()));
+
+            assert_eq!(wrapped.transfer(accounts.bob, 30, vec![1, 2, 3]), Ok(()));
+            assert_eq!(wrapped.balance_of(accounts.alice), 70);
+            assert_eq!(wrapped.balance_of(accounts.bob), 30);
+        }
+
+        #[ink::test]
+        fn withdraw_more_than_deposited_fails() {
+            let mut wrapped = WrappedNative::new(None, None, 12);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(wrapped.deposit(), Ok(()));
+
+            assert_eq!(wrapped.withdraw(101), Err(PSP22Error::InsufficientBalance));
+            assert_eq!(wrapped.total_supply(), 100);
+        }
+    }
+}
@@ -1,19 +1,113 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
+mod data;
+mod errors;
+mod receiver;
+
+pub use data::{PSP22Data, PSP22Event};
+pub use errors::{PSP22Error, PSP22ReceiverError};
+pub use receiver::check_receiver;
+pub use self::psp22_ink::{
+    AccessControl, AccessControlError, Compliance, FlashBorrower, FlashBorrowerError, Ownable, OwnableError, Pausable,
+    Psp22Ink, Psp22InkRef, PSP22Airdrop, PSP22Batch, PSP22Burnable, PSP22Capped, PSP22FlashLender, PSP22Metadata,
+    PSP22Mintable, PSP22Permit, PSP22Receiver, PSP22Snapshot, PSP22TimeLock, PSP22Vesting, PSP22Votes,
+    PSP22, RoleType, VestingSchedule, COMPLIANCE, DEFAULT_ADMIN_ROLE, FLASH_FEE_BPS, MAX_LOCKS_PER_ACCOUNT,
+    MAX_LOCK_DURATION, MINTER, PAUSER,
+};
+
 #[ink::contract]
 mod psp22_ink {
-    use ink::storage::Mapping;
+    use crate::{check_receiver, PSP22Data, PSP22Event, PSP22Error, PSP22ReceiverError};
+    use ink::storage::traits::StorageKey;
+    use ink::storage::{Lazy, Mapping};
     use ink::primitives::*;
     use ink::prelude::string::{String, ToString};
+    use ink::prelude::boxed::Box;
+    use ink::prelude::vec::Vec;
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::env::CallFlags;
+    use ink::env::hash::Blake2x256;
+
+    pub type RoleType = u32;
+
+    /// Admin role of every role unless changed; allowed to grant and revoke roles.
+    pub const DEFAULT_ADMIN_ROLE: RoleType = 0;
+    /// Allowed to mint new tokens.
+    pub const MINTER: RoleType = ink::selector_id!("MINTER");
+    /// Allowed to pause and unpause the contract.
+    pub const PAUSER: RoleType = ink::selector_id!("PAUSER");
+    /// Allowed to manage the blacklist and the allowlist.
+    pub const COMPLIANCE: RoleType = ink::selector_id!("COMPLIANCE");
+
+    //Roles granted to the deployer; they follow ownership so that a handover transfers full admin control.
+    const OWNER_ROLES: [RoleType; 4] = [DEFAULT_ADMIN_ROLE, MINTER, PAUSER, COMPLIANCE];
+
+    /// Fee charged on flash loans, in basis points of the borrowed amount.
+    pub const FLASH_FEE_BPS: Balance = 9;
+
+    /// Maximum number of active time locks with distinct unlock times held by one account.
+    pub const MAX_LOCKS_PER_ACCOUNT: usize = 32;
+
+    /// Longest time, in milliseconds from the current block, that tokens can be time-locked for (365 days).
+    pub const MAX_LOCK_DURATION: Timestamp = 365 * 24 * 60 * 60 * 1000;
 
     #[ink(storage)]
     #[derive(Default)]
     pub struct Psp22Ink {
-        total_supply: Balance,
+        data: PSP22Data,
+
+        name: Option<String>,
+
+        symbol: Option<String>,
+
+        decimals: u8,
+
+        roles: Mapping<(RoleType, AccountId), ()>,
+
+        role_admins: Mapping<RoleType, RoleType>,
+
+        owner: Option<AccountId>,
+
+        paused: bool,
+
+        nonces: Mapping<AccountId, u64>,
+
+        blacklist: Mapping<AccountId, ()>,
+
+        allowlist: Mapping<AccountId, ()>,
+
+        allowlist_only: bool,
+
+        cap: Balance,
+
+        balance_checkpoints: Mapping<(AccountId, u32), (BlockNumber, Balance)>,
+
+        balance_checkpoint_counts: Mapping<AccountId, u32>,
+
+        supply_checkpoints: Mapping<u32, (BlockNumber, Balance)>,
+
+        supply_checkpoint_count: u32,
+
+        delegates: Mapping<AccountId, AccountId>,
+
+        vote_checkpoints: Mapping<(AccountId, u32), (BlockNumber, Balance)>,
+
+        vote_checkpoint_counts: Mapping<AccountId, u32>,
+
+        vesting_schedules: Mapping<AccountId, VestingSchedule>,
+
+        locks: Mapping<AccountId, Vec<(Timestamp, Balance)>>,
+
+        merkle_root: Option<[u8; 32]>,
+
+        airdrop_pool: Balance,
+
+        airdrop_round: u32,
 
-        balances: Mapping<AccountId, Balance>,
+        claimed_bitmap: Mapping<(u32, u32), u128>,
 
-        allowances: Mapping<(AccountId, AccountId), Balance>,
+        //Set while a flash loan borrower is called; kept out of the root cell so re-entrant calls read it from storage.
+        flash_loan_lock: Lazy<bool>,
     }
 
     #[ink(event)]
@@ -34,24 +128,166 @@ mod psp22_ink {
         value: Balance,
     }
 
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        previous_owner: Option<AccountId>,
+        #[ink(topic)]
+        new_owner: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct BlacklistUpdated {
+        #[ink(topic)]
+        account: AccountId,
+        blacklisted: bool,
+    }
+
+    #[ink(event)]
+    pub struct AllowlistUpdated {
+        #[ink(topic)]
+        account: AccountId,
+        allowlisted: bool,
+    }
+
+    #[ink(event)]
+    pub struct AllowlistOnlyUpdated {
+        enabled: bool,
+    }
+
+    #[ink(event)]
+    pub struct DelegateChanged {
+        #[ink(topic)]
+        delegator: AccountId,
+        #[ink(topic)]
+        from_delegate: Option<AccountId>,
+        #[ink(topic)]
+        to_delegate: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct DelegateVotesChanged {
+        #[ink(topic)]
+        delegate: AccountId,
+        previous_votes: Balance,
+        new_votes: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Claimed {
+        #[ink(topic)]
+        account: AccountId,
+        index: u32,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        role: RoleType,
+        #[ink(topic)]
+        grantee: AccountId,
+        #[ink(topic)]
+        grantor: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct RoleAdminChanged {
+        #[ink(topic)]
+        role: RoleType,
+        #[ink(topic)]
+        previous_admin_role: RoleType,
+        #[ink(topic)]
+        new_admin_role: RoleType,
+    }
+
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: RoleType,
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        sender: AccountId,
+    }
+
+    pub type Result<T> = core::result::Result<T, PSP22Error>;
+
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-    pub enum PSP22Error {
-        /// Custom error type for cases in which an implementation adds its own restrictions.
-        Custom(String),
-        /// Returned if not enough balance to fulfill a request is available.
-        InsufficientBalance,
-        /// Returned if not enough allowance to fulfill a request is available.
-        InsufficientAllowance,
-        /// Returned if recipient's address is zero.
-        ZeroRecipientAddress,
-        /// Returned if sender's address is zero.
-        ZeroSenderAddress,
-        /// Returned if a safe transfer check fails (e.g. if the receiving contract does not accept tokens).
-        SafeTransferCheckFailed(String),
+    pub enum FlashBorrowerError {
+        /// Returned if the borrowing contract does not accept the flash loan.
+        FlashloanRejected(String),
     }
 
-    pub type Result<T> = core::result::Result<T, PSP22Error>;
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum OwnableError {
+        /// Returned if the caller is not the owner of the contract.
+        CallerIsNotOwner,
+        /// Returned if the new owner's address is zero.
+        NewOwnerIsZero,
+        /// Returned if called back by a flash loan borrower.
+        FlashLoanInProgress,
+    }
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum AccessControlError {
+        /// Returned if the caller is not allowed to act on behalf of the account.
+        InvalidCaller,
+        /// Returned if the account does not have the required role.
+        MissingRole,
+        /// Returned if the account already has the role being granted.
+        RoleRedundant,
+        /// Returned if called back by a flash loan borrower.
+        FlashLoanInProgress,
+    }
+
+    impl From<AccessControlError> for PSP22Error {
+        fn from(error: AccessControlError) -> Self {
+            match error {
+                AccessControlError::InvalidCaller => PSP22Error::Custom("InvalidCaller".to_string()),
+                AccessControlError::MissingRole => PSP22Error::Custom("MissingRole".to_string()),
+                AccessControlError::RoleRedundant => PSP22Error::Custom("RoleRedundant".to_string()),
+                AccessControlError::FlashLoanInProgress => PSP22Error::FlashLoanInProgress,
+            }
+        }
+    }
+
+    impl From<OwnableError> for PSP22Error {
+        fn from(error: OwnableError) -> Self {
+            match error {
+                OwnableError::CallerIsNotOwner => PSP22Error::Custom("CallerIsNotOwner".to_string()),
+                OwnableError::NewOwnerIsZero => PSP22Error::Custom("NewOwnerIsZero".to_string()),
+                OwnableError::FlashLoanInProgress => PSP22Error::FlashLoanInProgress,
+            }
+        }
+    }
+
+    /// Tokens locked for a beneficiary and released linearly over `duration` from `start`,
+    /// with nothing released before `start + cliff`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct VestingSchedule {
+        pub total: Balance,
+        pub released: Balance,
+        pub start: Timestamp,
+        pub cliff: Timestamp,
+        pub duration: Timestamp,
+    }
 
     #[ink::trait_definition]
     pub trait PSP22{
@@ -65,10 +301,10 @@ mod psp22_ink {
         fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance;
 
         #[ink(message)]
-        fn transfer(&mut self, to: AccountId, value: Balance) -> Result<()>;
+        fn transfer(&mut self, to: AccountId, value: Balance, data: Vec<u8>) -> Result<()>;
 
         #[ink(message)]
-        fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<()>;
+        fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance, data: Vec<u8>) -> Result<()>;
 
         #[ink(message)]
         fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()>;
@@ -80,179 +316,3697 @@ mod psp22_ink {
         fn decrease_allowance(&mut self, spender: AccountId, subtracted_value: Balance) -> Result<()>;
     }
 
-    impl Psp22Ink {
+    #[ink::trait_definition]
+    pub trait PSP22Receiver{
+        /// Called by a PSP22 token contract when `value` tokens are transferred from `from` to this contract by `operator`.
+        /// Returning an error rejects the transfer.
+        #[ink(message)]
+        fn before_received(&mut self, operator: AccountId, from: AccountId, value: Balance, data: Vec<u8>) -> core::result::Result<(), PSP22ReceiverError>;
+    }
 
-        #[ink(constructor)]
-        pub fn new(total_supply : Balance) -> Self {
-            let mut balances = Mapping::default();
-            balances.insert(Self::env().caller(), &total_supply);
-            Self::env().emit_event(Transfer {
-                from: None,
-                to: Some(Self::env().caller()),
-                value: total_supply,
-            });
-            Self {
-                balances,
-                total_supply,
-                allowances: Mapping::default(),
-            }
-        }
+    #[ink::trait_definition]
+    pub trait FlashBorrower{
+        /// Called by a PSP22 token contract after minting `amount` tokens to this contract on behalf of `initiator`.
+        /// Before returning, this contract must allow the token contract to spend `amount + fee` of its tokens.
+        /// Returning an error rejects the flash loan.
+        #[ink(message)]
+        fn on_flash_loan(&mut self, initiator: AccountId, token: AccountId, amount: Balance, fee: Balance, data: Vec<u8>) -> core::result::Result<(), FlashBorrowerError>;
     }
 
-    impl PSP22 for Psp22Ink{
+    #[ink::trait_definition]
+    pub trait PSP22Metadata{
         #[ink(message)]
-        fn total_supply(&self) -> Balance {
-            self.total_supply
-        }
+        fn token_name(&self) -> Option<String>;
 
         #[ink(message)]
-        fn balance_of(&self, owner: AccountId) -> Balance {
-            self.balances.get(&owner).unwrap_or_default()
-        }
+        fn token_symbol(&self) -> Option<String>;
+
         #[ink(message)]
-        fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
-            self.allowances.get(&(owner, spender)).unwrap_or_default()
-        }
+        fn token_decimals(&self) -> u8;
+    }
+
+    #[ink::trait_definition]
+    pub trait PSP22Mintable{
         #[ink(message)]
-        fn transfer(&mut self, to: AccountId, value: Balance) -> Result<()> {
-            let from = self.env().caller();
-            //Reverts with error `InsufficientBalance` if there are not enough tokens on, the caller's account Balance.
-            let from_balance = self.balance_of(from);
-            if from_balance < value {
-                return Err(PSP22Error::InsufficientBalance);
-            }
-            //Reverts with error `ZeroSenderAddress` if sender's address is zero.
-            if from == AccountId::from([0x0; 32]) {
-                return Err(PSP22Error::ZeroSenderAddress);
-            }
-            //Reverts with error `ZeroRecipientAddress` if recipient's address is zero.
-            if to == AccountId::from([0x0; 32]) {
-                return Err(PSP22Error::ZeroRecipientAddress);
-            }
-            //Reverts with error `SafeTransferCheckFailed` if the recipient is a contract and rejected the transfer.
-            if self.env().is_contract(&to){
-                return Err(PSP22Error::SafeTransferCheckFailed(
-                    "Recipient is a contract and does not implement safe transfer behavior".to_string(),
-                ));
-            }
-            //Decreases the balance of `from` and increases the balance of `to` by the same amount.
-            self.balances.insert(from, &(from_balance - value));
-            let to_balance = self.balance_of(to);
-            self.balances.insert(to, &(to_balance + value));
-            //Emits a `Transfer` event with `from` set to `None` if the sender is the zero address, otherwise to `Some(sender)`.
-            self.env().emit_event(Transfer {
-                from: Some(from),
-                to: Some(to),
-                value,
-            });
-            Ok(())
+        fn mint(&mut self, to: AccountId, value: Balance) -> Result<()>;
+    }
 
-        }
+    #[ink::trait_definition]
+    pub trait PSP22Capped{
+        /// Returns the maximum total supply of the token.
+        #[ink(message)]
+        fn cap(&self) -> Balance;
+    }
 
+    #[ink::trait_definition]
+    pub trait PSP22Burnable{
         #[ink(message)]
-        fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
-            let caller = self.env().caller();
-            let allowance = self.allowance(from, caller);
-            //Reverts with error `InsufficientAllowance` if there are not enough tokens allowed for the caller's account.
-            if allowance < value {
-                return Err(PSP22Error::InsufficientAllowance);
-            }
-            //Reverts with error `InsufficientBalance` if there are not enough tokens on the `from` account.
-            let from_balance = self.balance_of(from);
-            if from_balance < value {
-                return Err(PSP22Error::InsufficientBalance);
-            }
-            //Reverts with error `ZeroSenderAddress` if sender's address is zero.
-            if from == AccountId::from([0x0; 32]) {
-                return Err(PSP22Error::ZeroSenderAddress);
-            }
-            //Reverts with error `ZeroRecipientAddress` if recipient's address is zero.
-            if to == AccountId::from([0x0; 32]) {
-                return Err(PSP22Error::ZeroRecipientAddress);
-            }
-            //Reverts with error `SafeTransferCheckFailed` if the recipient is a contract and rejected the transfer.
-            if self.env().is_contract(&to) {
-                return Err(PSP22Error::SafeTransferCheckFailed(
-                    "Recipient is a contract and does not implement safe transfer behavior".to_string(),
-                ));
-            }
-            //Decreases the allowance by the transferred amount.
-            self.allowances.insert((from, caller), &(allowance - value));
-            //Decreases the balance of `from` and increases the balance of `to` by the same amount.
-            self.balances.insert(from, &(from_balance - value));
-            let to_balance = self.balance_of(to);
-            self.balances.insert(to, &(to_balance + value));
-            //Emits a `Transfer` event with `from` set to `None` if the sender is the zero address, otherwise to `Some(sender)`.
-            self.env().emit_event(Transfer {
-                from: Some(from),
-                to: Some(to),
-                value,
-            });
-            Ok(())
-        }
+        fn burn(&mut self, value: Balance) -> Result<()>;
 
         #[ink(message)]
-        fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
-            let owner = self.env().caller();
-            //Sets `value` as the allowance of `spender` over the caller's tokens.
-            self.allowances.insert((&owner, &spender), &value);
-            //Emits an `Approval` event.
-            self.env().emit_event(Approval {
-                owner,
-                spender,
-                value,
-            });
-            Ok(())
-        }
+        fn burn_from(&mut self, account: AccountId, value: Balance) -> Result<()>;
+    }
 
+    #[ink::trait_definition]
+    pub trait Ownable{
         #[ink(message)]
-        fn increase_allowance(&mut self, spender: AccountId, added_value: Balance) -> Result<()> {
-            let owner = self.env().caller();
-            //Increases the allowance of `spender` over the caller's tokens by `added_value`.
-            let allowance = self.allowance(owner, spender);
-
-            //Reverts with error `ZeroSenderAddress` if sender's address is zero.
-            if owner == AccountId::from([0x0; 32]) {
-                return Err(PSP22Error::ZeroSenderAddress);
-            }
-            //Reverts with error `ZeroRecipientAddress` if recipient's address is zero.
-            if spender == AccountId::from([0x0; 32]) {
-                return Err(PSP22Error::ZeroRecipientAddress);
-            }
-            self.allowances.insert((&owner, &spender), &(allowance + added_value));
-            //Emits an `Approval` event.
-            self.env().emit_event(Approval {
-                owner,
-                spender,
-                value: allowance + added_value,
-            });
-            Ok(())
-        }
+        fn owner(&self) -> Option<AccountId>;
 
+        /// Makes `new_owner` the owner and moves the admin, minter, pauser and compliance roles
+        /// held by the current owner to `new_owner`.
         #[ink(message)]
-        fn decrease_allowance(&mut self, spender: AccountId, subtracted_value: Balance) -> Result<()> {
-            let owner = self.env().caller();
-            //Decreases the allowance of `spender` over the caller's tokens by `subtracted_value`.
-            let allowance = self.allowance(owner, spender);
-            //Reverts with error `InsufficientAllowance` if there are not enough tokens allowed for the caller's account.
-            if allowance < subtracted_value {
-                return Err(PSP22Error::InsufficientAllowance);
-            }
-            //Reverts with error `ZeroSenderAddress` if sender's address is zero.
-            if owner == AccountId::from([0x0; 32]) {
-                return Err(PSP22Error::ZeroSenderAddress);
-            }
-            //Reverts with error `ZeroRecipientAddress` if recipient's address is zero.
-            if spender == AccountId::from([0x0; 32]) {
-                return Err(PSP22Error::ZeroRecipientAddress);
-            }
-            self.allowances.insert((&owner, &spender), &(allowance - subtracted_value));
-            //Emits an `Approval` event.
-            self.env().emit_event(Approval {
-                owner,
-                spender,
-                value: allowance - subtracted_value,
-            });
+        fn transfer_ownership(&mut self, new_owner: AccountId) -> core::result::Result<(), OwnableError>;
+
+        /// Leaves the contract without an owner and revokes the admin, minter, pauser and compliance
+        /// roles held by the current owner.
+        #[ink(message)]
+        fn renounce_ownership(&mut self) -> core::result::Result<(), OwnableError>;
+    }
+
+    #[ink::trait_definition]
+    pub trait AccessControl{
+        #[ink(message)]
+        fn has_role(&self, role: RoleType, address: AccountId) -> bool;
+
+        #[ink(message)]
+        fn get_role_admin(&self, role: RoleType) -> RoleType;
+
+        #[ink(message)]
+        fn grant_role(&mut self, role: RoleType, account: AccountId) -> core::result::Result<(), AccessControlError>;
+
+        #[ink(message)]
+        fn revoke_role(&mut self, role: RoleType, account: AccountId) -> core::result::Result<(), AccessControlError>;
+
+        #[ink(message)]
+        fn renounce_role(&mut self, role: RoleType, account: AccountId) -> core::result::Result<(), AccessControlError>;
+
+        /// Makes `admin_role` the role allowed to grant and revoke `role`.
+        #[ink(message)]
+        fn set_role_admin(&mut self, role: RoleType, admin_role: RoleType) -> core::result::Result<(), AccessControlError>;
+    }
+
+    #[ink::trait_definition]
+    pub trait Pausable{
+        #[ink(message)]
+        fn paused(&self) -> bool;
+
+        #[ink(message)]
+        fn pause(&mut self) -> Result<()>;
+
+        #[ink(message)]
+        fn unpause(&mut self) -> Result<()>;
+    }
+
+    #[ink::trait_definition]
+    pub trait PSP22Permit{
+        /// Sets `value` as the allowance of `spender` over `owner`'s tokens, authorized by an ECDSA `signature` of `owner`
+        /// over the domain-separated permit payload, so that anyone can submit the approval on the owner's behalf.
+        #[ink(message)]
+        fn permit(&mut self, owner: AccountId, spender: AccountId, value: Balance, deadline: Timestamp, signature: [u8; 65]) -> Result<()>;
+
+        /// Returns the nonce that the next permit of `owner` has to be signed with.
+        #[ink(message)]
+        fn nonces(&self, owner: AccountId) -> u64;
+    }
+
+    #[ink::trait_definition]
+    pub trait Compliance{
+        #[ink(message)]
+        fn is_blacklisted(&self, account: AccountId) -> bool;
+
+        #[ink(message)]
+        fn add_to_blacklist(&mut self, account: AccountId) -> Result<()>;
+
+        #[ink(message)]
+        fn remove_from_blacklist(&mut self, account: AccountId) -> Result<()>;
+
+        #[ink(message)]
+        fn is_allowlisted(&self, account: AccountId) -> bool;
+
+        #[ink(message)]
+        fn add_to_allowlist(&mut self, account: AccountId) -> Result<()>;
+
+        #[ink(message)]
+        fn remove_from_allowlist(&mut self, account: AccountId) -> Result<()>;
+
+        /// Returns whether only allowlisted accounts may send and receive tokens.
+        #[ink(message)]
+        fn allowlist_only(&self) -> bool;
+
+        #[ink(message)]
+        fn set_allowlist_only(&mut self, enabled: bool) -> Result<()>;
+    }
+
+    #[ink::trait_definition]
+    pub trait PSP22Snapshot{
+        /// Returns the balance of `account` at the end of `block`.
+        #[ink(message)]
+        fn balance_of_at(&self, account: AccountId, block: BlockNumber) -> Balance;
+
+        /// Returns the total supply at the end of `block`.
+        #[ink(message)]
+        fn total_supply_at(&self, block: BlockNumber) -> Balance;
+    }
+
+    #[ink::trait_definition]
+    pub trait PSP22Votes{
+        /// Delegates the voting power of the caller's balance to `delegatee`.
+        #[ink(message)]
+        fn delegate(&mut self, delegatee: AccountId) -> Result<()>;
+
+        /// Returns the account `account` delegates its voting power to, if any.
+        #[ink(message)]
+        fn delegates(&self, account: AccountId) -> Option<AccountId>;
+
+        /// Returns the current voting power of `account`.
+        #[ink(message)]
+        fn get_votes(&self, account: AccountId) -> Balance;
+
+        /// Returns the voting power of `account` at the end of `block`.
+        #[ink(message)]
+        fn get_past_votes(&self, account: AccountId, block: BlockNumber) -> Balance;
+    }
+
+    #[ink::trait_definition]
+    pub trait PSP22FlashLender{
+        /// Returns the maximum amount of tokens available for a flash loan.
+        #[ink(message)]
+        fn max_flash_loan(&self) -> Balance;
+
+        /// Returns the fee charged for a flash loan of `amount` tokens.
+        #[ink(message)]
+        fn flash_fee(&self, amount: Balance) -> Balance;
+
+        /// Mints `amount` tokens to `receiver`, calls `FlashBorrower::on_flash_loan` on it and then burns
+        /// `amount` plus the fee from `receiver` using the allowance it gave to this contract.
+        /// While the borrower is called, it can move the borrowed tokens freely but cannot take another
+        /// flash loan or change ownership, roles, pausing or compliance settings of this contract.
+        #[ink(message)]
+        fn flash_loan(&mut self, receiver: AccountId, amount: Balance, data: Vec<u8>) -> Result<()>;
+    }
+
+    #[ink::trait_definition]
+    pub trait PSP22Vesting{
+        /// Moves `amount` tokens of the owner into this contract and vests them to `beneficiary`
+        /// linearly over `duration` from `start`, with nothing vested before `start + cliff`.
+        /// A previous schedule of `beneficiary` is replaced once it is fully released.
+        #[ink(message)]
+        fn create_vesting_schedule(
+            &mut self,
+            beneficiary: AccountId,
+            amount: Balance,
+            start: Timestamp,
+            cliff: Timestamp,
+            duration: Timestamp,
+        ) -> Result<()>;
+
+        #[ink(message)]
+        fn vesting_schedule(&self, beneficiary: AccountId) -> Option<VestingSchedule>;
+
+        /// Returns the amount of tokens vested to `beneficiary` so far, including released ones.
+        #[ink(message)]
+        fn vested_amount(&self, beneficiary: AccountId) -> Balance;
+
+        /// Returns the amount of vested tokens not yet released to `beneficiary`.
+        #[ink(message)]
+        fn releasable(&self, beneficiary: AccountId) -> Balance;
+
+        /// Transfers the releasable tokens to `beneficiary`.
+        #[ink(message)]
+        fn release(&mut self, beneficiary: AccountId) -> Result<()>;
+    }
+
+    #[ink::trait_definition]
+    pub trait PSP22TimeLock{
+        /// Transfers `value` tokens to `to` that `to` cannot move before `unlock_at`.
+        /// Locks with the same `unlock_at` are merged; at most `MAX_LOCKS_PER_ACCOUNT` can be active,
+        /// and `unlock_at` can be at most `MAX_LOCK_DURATION` ahead of the current block.
+        #[ink(message)]
+        fn transfer_locked(&mut self, to: AccountId, value: Balance, unlock_at: Timestamp) -> Result<()>;
+
+        /// Returns the part of the balance of `account` that is still locked.
+        #[ink(message)]
+        fn locked_balance_of(&self, account: AccountId) -> Balance;
+
+        /// Returns the part of the balance of `account` that can be moved.
+        #[ink(message)]
+        fn unlocked_balance_of(&self, account: AccountId) -> Balance;
+    }
+
+    #[ink::trait_definition]
+    pub trait PSP22Batch{
+        /// Transfers each `(to, value)` pair of `recipients` from the caller, atomically.
+        #[ink(message)]
+        fn batch_transfer(&mut self, recipients: Vec<(AccountId, Balance)>) -> Result<()>;
+
+        /// Transfers each `(to, value)` pair of `recipients` from `from` on behalf of the caller, atomically.
+        #[ink(message)]
+        fn batch_transfer_from(&mut self, from: AccountId, recipients: Vec<(AccountId, Balance)>) -> Result<()>;
+    }
+
+    #[ink::trait_definition]
+    pub trait PSP22Airdrop{
+        /// Starts a new airdrop round with the Merkle root of the `(index, account, amount)` claims and
+        /// `amount` tokens of the owner as its pool. Tokens left in the pool of the previous round are
+        /// returned to the owner, and claimed indices are tracked per round.
+        ///
+        /// A leaf is `Blake2x256` of the SCALE encoding of `(index: u32, account: AccountId, amount: Balance)`.
+        /// A parent is `Blake2x256` of the concatenation of its two children, the smaller one first.
+        #[ink(message)]
+        fn set_merkle_root(&mut self, merkle_root: [u8; 32], amount: Balance) -> Result<()>;
+
+        #[ink(message)]
+        fn merkle_root(&self) -> Option<[u8; 32]>;
+
+        /// Returns the amount of tokens left in the airdrop pool.
+        #[ink(message)]
+        fn airdrop_pool(&self) -> Balance;
+
+        /// Returns whether the claim at `index` of the current round was made.
+        #[ink(message)]
+        fn is_claimed(&self, index: u32) -> bool;
+
+        /// Transfers `amount` tokens from the airdrop pool to `account` if `proof` shows that
+        /// the claim `(index, account, amount)` is part of the Merkle tree.
+        #[ink(message)]
+        fn claim(&mut self, index: u32, account: AccountId, amount: Balance, proof: Vec<[u8; 32]>) -> Result<()>;
+    }
+
+    impl Psp22Ink {
+
+        #[ink(constructor)]
+        pub fn new(total_supply : Balance) -> Self {
+            Self::new_with_metadata(total_supply, None, None, 0)
+        }
+
+        #[ink(constructor)]
+        pub fn new_with_metadata(total_supply : Balance, name: Option<String>, symbol: Option<String>, decimals: u8) -> Self {
+            Self::init(total_supply, Balance::MAX, name, symbol, decimals)
+        }
+
+        /// Creates a token whose total supply can never exceed `cap`.
+        #[ink(constructor)]
+        pub fn new_capped(total_supply : Balance, cap: Balance, name: Option<String>, symbol: Option<String>, decimals: u8) -> Result<Self> {
+            //Reverts with error `CapExceeded` if the initial supply is above the cap.
+            if total_supply > cap {
+                return Err(PSP22Error::CapExceeded);
+            }
+            Ok(Self::init(total_supply, cap, name, symbol, decimals))
+        }
+
+        //Mints the initial supply to the deployer and sets up ownership and roles.
+        fn init(total_supply : Balance, cap: Balance, name: Option<String>, symbol: Option<String>, decimals: u8) -> Self {
+            let (data, events) = PSP22Data::new(total_supply, Self::env().caller());
+            let mut instance = Self {
+                data,
+                name,
+                symbol,
+                decimals,
+                roles: Mapping::default(),
+                role_admins: Mapping::default(),
+                owner: Some(Self::env().caller()),
+                paused: false,
+                nonces: Mapping::default(),
+                blacklist: Mapping::default(),
+                allowlist: Mapping::default(),
+                allowlist_only: false,
+                cap,
+                balance_checkpoints: Mapping::default(),
+                balance_checkpoint_counts: Mapping::default(),
+                supply_checkpoints: Mapping::default(),
+                supply_checkpoint_count: 0,
+                delegates: Mapping::default(),
+                vote_checkpoints: Mapping::default(),
+                vote_checkpoint_counts: Mapping::default(),
+                vesting_schedules: Mapping::default(),
+                locks: Mapping::default(),
+                merkle_root: None,
+                airdrop_pool: 0,
+                airdrop_round: 0,
+                claimed_bitmap: Mapping::default(),
+                flash_loan_lock: Lazy::default(),
+            };
+            instance
+                .emit_events(events)
+                .expect("the initial supply cannot overflow the empty checkpoints");
+            //The deployer is the initial owner.
+            Self::env().emit_event(OwnershipTransferred {
+                previous_owner: None,
+                new_owner: Some(Self::env().caller()),
+            });
+            //The deployer is the initial admin, minter, pauser and compliance officer.
+            for role in OWNER_ROLES {
+                instance.do_grant_role(role, Self::env().caller(), None);
+            }
+            instance
+        }
+
+        //Emits the contract events corresponding to events returned by `PSP22Data`.
+        fn emit_events(&mut self, events: Vec<PSP22Event>) -> Result<()> {
+            for event in events {
+                match event {
+                    PSP22Event::Transfer { from, to, value } => {
+                        self.write_checkpoints(from, to)?;
+                        let from_delegate = from.and_then(|account| self.delegates.get(account));
+                        let to_delegate = to.and_then(|account| self.delegates.get(account));
+                        self.move_voting_power(from_delegate, to_delegate, value)?;
+                        self.env().emit_event(Transfer { from, to, value })
+                    }
+                    PSP22Event::Approval { owner, spender, value } => {
+                        self.env().emit_event(Approval { owner, spender, value })
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        //Reverts with error `Paused` if the contract is paused.
+        fn ensure_not_paused(&self) -> Result<()> {
+            if self.paused {
+                return Err(PSP22Error::Paused);
+            }
+            Ok(())
+        }
+
+        //Reverts with error `MissingRole` if `account` does not have `role`.
+        fn ensure_role(&self, role: RoleType, account: AccountId) -> core::result::Result<(), AccessControlError> {
+            if !self.roles.contains((role, account)) {
+                return Err(AccessControlError::MissingRole);
+            }
+            Ok(())
+        }
+
+        //Grants `role` to `account` and emits a `RoleGranted` event.
+        fn do_grant_role(&mut self, role: RoleType, account: AccountId, grantor: Option<AccountId>) {
+            self.roles.insert((role, account), &());
+            self.env().emit_event(RoleGranted {
+                role,
+                grantee: account,
+                grantor,
+            });
+        }
+
+        //Revokes `role` from `account` and emits a `RoleRevoked` event.
+        fn do_revoke_role(&mut self, role: RoleType, account: AccountId) {
+            self.roles.remove((role, account));
+            self.env().emit_event(RoleRevoked {
+                role,
+                account,
+                sender: self.env().caller(),
+            });
+        }
+
+        //Reverts with error `CallerIsNotOwner` if the caller is not the owner of the contract.
+        fn only_owner(&self) -> core::result::Result<(), OwnableError> {
+            if self.owner != Some(self.env().caller()) {
+                return Err(OwnableError::CallerIsNotOwner);
+            }
+            Ok(())
+        }
+
+        //Sets `new_owner` as the owner of the contract and emits an `OwnershipTransferred` event.
+        //The owner roles held by the previous owner are revoked and, if there is a new owner, granted to it.
+        fn set_owner(&mut self, new_owner: Option<AccountId>) {
+            let previous_owner = self.owner;
+            for role in OWNER_ROLES {
+                if let Some(previous_owner) = previous_owner {
+                    if !self.roles.contains((role, previous_owner)) {
+                        continue;
+                    }
+                    self.do_revoke_role(role, previous_owner);
+                }
+                if let Some(new_owner) = new_owner {
+                    if !self.roles.contains((role, new_owner)) {
+                        self.do_grant_role(role, new_owner, previous_owner);
+                    }
+                }
+            }
+            self.owner = new_owner;
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner,
+                new_owner,
+            });
+        }
+
+        //Runs `check_receiver` with the caller as the operator.
+        fn do_safe_transfer_check(&self, from: AccountId, to: AccountId, value: Balance, data: Vec<u8>) -> Result<()> {
+            check_receiver(self.env().caller(), from, to, value, data)
+        }
+
+        //Returns the hash `owner` has to sign to permit `spender` to spend `value` tokens until `deadline`.
+        //The payload is bound to this contract so that a signature cannot be replayed on another token.
+        fn permit_hash(&self, owner: AccountId, spender: AccountId, value: Balance, nonce: u64, deadline: Timestamp) -> [u8; 32] {
+            let domain = (b"PSP22Permit", self.env().account_id());
+            self.env().hash_encoded::<Blake2x256, _>(&(domain, owner, spender, value, nonce, deadline))
+        }
+
+        //Reverts with error `Blacklisted` if `from` or `to` is blacklisted, or with error `NotAllowlisted`
+        //if the contract is in allowlist-only mode and `from` or `to` is not allowlisted.
+        fn ensure_compliant(&self, from: Option<AccountId>, to: Option<AccountId>) -> Result<()> {
+            for account in [from, to].into_iter().flatten() {
+                if self.blacklist.contains(account) {
+                    return Err(PSP22Error::Blacklisted(account));
+                }
+                if self.allowlist_only && !self.allowlist.contains(account) {
+                    return Err(PSP22Error::NotAllowlisted(account));
+                }
+            }
+            Ok(())
+        }
+
+        //Records the current balances of `from` and `to` and, for mints and burns, the current total supply
+        //as checkpoints of the current block, overwriting checkpoints already written in this block.
+        fn write_checkpoints(&mut self, from: Option<AccountId>, to: Option<AccountId>) -> Result<()> {
+            let block = self.env().block_number();
+            for account in [from, to].into_iter().flatten() {
+                Self::push_checkpoint(
+                    &mut self.balance_checkpoints,
+                    &mut self.balance_checkpoint_counts,
+                    account,
+                    block,
+                    self.data.balance_of(account),
+                )?;
+            }
+            if from.is_none() || to.is_none() {
+                let checkpoint = (block, self.data.total_supply());
+                let count = self.supply_checkpoint_count;
+                match count.checked_sub(1).and_then(|last| self.supply_checkpoints.get(last)) {
+                    Some((last_block, _)) if last_block == block => {
+                        self.supply_checkpoints.insert(count - 1, &checkpoint);
+                    }
+                    _ => {
+                        //Reverts with error `Overflow` if the number of checkpoints does not fit into `u32`.
+                        self.supply_checkpoint_count = count.checked_add(1).ok_or(PSP22Error::Overflow)?;
+                        self.supply_checkpoints.insert(count, &checkpoint);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        //Records `value` as the checkpoint of `account` for `block`, overwriting a checkpoint already written in this block.
+        fn push_checkpoint(
+            checkpoints: &mut Mapping<(AccountId, u32), (BlockNumber, Balance)>,
+            counts: &mut Mapping<AccountId, u32>,
+            account: AccountId,
+            block: BlockNumber,
+            value: Balance,
+        ) -> Result<()> {
+            let count = counts.get(account).unwrap_or_default();
+            match count.checked_sub(1).and_then(|last| checkpoints.get((account, last))) {
+                Some((last_block, _)) if last_block == block => {
+                    checkpoints.insert((account, count - 1), &(block, value));
+                }
+                _ => {
+                    //Reverts with error `Overflow` if the number of checkpoints does not fit into `u32`.
+                    let next_count = count.checked_add(1).ok_or(PSP22Error::Overflow)?;
+                    checkpoints.insert((account, count), &(block, value));
+                    counts.insert(account, &next_count);
+                }
+            }
+            Ok(())
+        }
+
+        //Moves `value` votes from delegate `from` to delegate `to`; a missing delegate holds no votes.
+        fn move_voting_power(&mut self, from: Option<AccountId>, to: Option<AccountId>, value: Balance) -> Result<()> {
+            if from == to || value == 0 {
+                return Ok(());
+            }
+            if let Some(delegate) = from {
+                let previous_votes = self.get_votes(delegate);
+                //Reverts with error `Overflow` if the delegate has fewer votes than are moved away,
+                //which would mean the vote accounting is broken.
+                let new_votes = previous_votes.checked_sub(value).ok_or(PSP22Error::Overflow)?;
+                self.write_votes(delegate, previous_votes, new_votes)?;
+            }
+            if let Some(delegate) = to {
+                let previous_votes = self.get_votes(delegate);
+                //Reverts with error `Overflow` if the votes do not fit into `u128`.
+                let new_votes = previous_votes.checked_add(value).ok_or(PSP22Error::Overflow)?;
+                self.write_votes(delegate, previous_votes, new_votes)?;
+            }
+            Ok(())
+        }
+
+        fn write_votes(&mut self, delegate: AccountId, previous_votes: Balance, new_votes: Balance) -> Result<()> {
+            let block = self.env().block_number();
+            Self::push_checkpoint(
+                &mut self.vote_checkpoints,
+                &mut self.vote_checkpoint_counts,
+                delegate,
+                block,
+                new_votes,
+            )?;
+            self.env().emit_event(DelegateVotesChanged {
+                delegate,
+                previous_votes,
+                new_votes,
+            });
+            Ok(())
+        }
+
+        //Returns the value of the last of `count` checkpoints recorded at or before `block`, or zero if there is none.
+        //`checkpoint(i)` returns the `i`-th checkpoint; checkpoints are ordered by block number.
+        fn upper_lookup(
+            count: u32,
+            block: BlockNumber,
+            checkpoint: impl Fn(u32) -> Option<(BlockNumber, Balance)>,
+        ) -> Balance {
+            let (mut low, mut high) = (0, count);
+            while low < high {
+                let mid = low + (high - low) / 2;
+                match checkpoint(mid) {
+                    Some((checkpoint_block, _)) if checkpoint_block > block => high = mid,
+                    _ => low = mid + 1,
+                }
+            }
+            high.checked_sub(1)
+                .and_then(checkpoint)
+                .map(|(_, value)| value)
+                .unwrap_or_default()
+        }
+
+        fn do_flash_loan_callback(&mut self, receiver: AccountId, amount: Balance, fee: Balance, data: Vec<u8>) -> Result<()> {
+            let call_result = build_call::<ink::env::DefaultEnvironment>()
+                .call(receiver)
+                //The borrower calls back into this contract to approve the repayment.
+                .call_flags(CallFlags::default().set_allow_reentry(true))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("FlashBorrower::on_flash_loan")))
+                        .push_arg(self.env().caller())
+                        .push_arg(self.env().account_id())
+                        .push_arg(amount)
+                        .push_arg(fee)
+                        .push_arg(data),
+                )
+                .returns::<core::result::Result<(), FlashBorrowerError>>()
+                .try_invoke();
+            match call_result {
+                Ok(Ok(Ok(()))) => Ok(()),
+                Ok(Ok(Err(FlashBorrowerError::FlashloanRejected(reason)))) => Err(PSP22Error::FlashLoanRejected(reason)),
+                _ => Err(PSP22Error::FlashLoanRejected(
+                    "Receiver does not implement FlashBorrower".to_string(),
+                )),
+            }
+        }
+
+        //Returns the part of `schedule.total` vested at the current block timestamp.
+        fn vested_at_now(&self, schedule: &VestingSchedule) -> Balance {
+            let now = self.env().block_timestamp();
+            if now < schedule.start.saturating_add(schedule.cliff) {
+                return 0;
+            }
+            let elapsed = Balance::from(now - schedule.start);
+            let duration = Balance::from(schedule.duration);
+            if elapsed >= duration {
+                return schedule.total;
+            }
+            //Computes `total * elapsed / duration` without overflowing.
+            schedule.total / duration * elapsed + schedule.total % duration * elapsed / duration
+        }
+
+        //Reverts with error `BalanceLocked` if `account` holds `value` tokens but part of them is still locked.
+        //A balance that is too low is left to be reported by `PSP22Data`.
+        fn ensure_unlocked(&self, account: AccountId, value: Balance) -> Result<()> {
+            if self.data.balance_of(account) >= value && self.unlocked_balance_of(account) < value {
+                return Err(PSP22Error::BalanceLocked);
+            }
+            Ok(())
+        }
+
+        //Transfers each `(to, value)` pair of `recipients` from `from`, spending the allowance of `spender` if given.
+        //All recipients and the total are validated before any balance is touched.
+        fn do_batch_transfer(&mut self, from: AccountId, recipients: Vec<(AccountId, Balance)>, spender: Option<AccountId>) -> Result<()> {
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the sender or the spender is restricted.
+            self.ensure_compliant(Some(from), spender)?;
+            let mut total: Balance = 0;
+            for (index, &(to, value)) in recipients.iter().enumerate() {
+                //Reverts with error `BatchTransferFailed` if a recipient is zero or restricted, or if the total overflows.
+                let checked = if to == AccountId::from([0x0; 32]) {
+                    Err(PSP22Error::ZeroRecipientAddress)
+                } else {
+                    self.ensure_compliant(None, Some(to))
+                };
+                total = checked
+                    .and_then(|()| total.checked_add(value).ok_or(PSP22Error::Overflow))
+                    .map_err(|error| PSP22Error::BatchTransferFailed(index as u32, Box::new(error)))?;
+            }
+            //Reverts with error `InsufficientAllowance` if the spender is not allowed to move the total.
+            if let Some(spender) = spender {
+                if self.data.allowance(from, spender) < total {
+                    return Err(PSP22Error::InsufficientAllowance);
+                }
+            }
+            //Reverts with error `InsufficientBalance` if `from` does not hold the total.
+            if self.data.balance_of(from) < total {
+                return Err(PSP22Error::InsufficientBalance);
+            }
+            //Reverts with error `BalanceLocked` if the tokens are held but still locked.
+            self.ensure_unlocked(from, total)?;
+            for (index, (to, value)) in recipients.into_iter().enumerate() {
+                let events = match spender {
+                    Some(spender) => self.data.transfer_from(spender, from, to, value),
+                    None => self.data.transfer(from, to, value),
+                }
+                .and_then(|events| {
+                    //Reverts with error `BatchTransferFailed` if a recipient is a contract and rejected the transfer.
+                    self.do_safe_transfer_check(from, to, value, Vec::new())?;
+                    Ok(events)
+                })
+                .map_err(|error| PSP22Error::BatchTransferFailed(index as u32, Box::new(error)))?;
+                self.emit_events(events)?;
+            }
+            Ok(())
+        }
+
+        //Returns the Merkle tree leaf of the claim of `amount` tokens by `account` at `index`.
+        fn merkle_leaf(&self, index: u32, account: AccountId, amount: Balance) -> [u8; 32] {
+            self.env().hash_encoded::<Blake2x256, _>(&(index, account, amount))
+        }
+
+        //Returns the parent of two Merkle tree nodes; the pair is sorted so that proofs need no position bits.
+        fn merkle_parent(&self, a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+            let (first, second) = if a <= b { (a, b) } else { (b, a) };
+            let mut input = [0u8; 64];
+            input[..32].copy_from_slice(&first);
+            input[32..].copy_from_slice(&second);
+            self.env().hash_bytes::<Blake2x256>(&input)
+        }
+
+        //Reverts with `error` while a flash loan borrower is called, so that it cannot nest loans or change admin settings.
+        fn ensure_no_flash_loan<E>(&self, error: E) -> core::result::Result<(), E> {
+            if self.flash_loan_lock.get().unwrap_or_default() {
+                return Err(error);
+            }
+            Ok(())
+        }
+    }
+
+    impl PSP22 for Psp22Ink{
+        #[ink(message)]
+        fn total_supply(&self) -> Balance {
+            self.data.total_supply()
+        }
+
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> Balance {
+            self.data.balance_of(owner)
+        }
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            self.data.allowance(owner, spender)
+        }
+
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, value: Balance, data: Vec<u8>) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            let from = self.env().caller();
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the sender or the recipient is restricted.
+            self.ensure_compliant(Some(from), Some(to))?;
+            //Reverts with error `BalanceLocked` if the tokens are held but still locked.
+            self.ensure_unlocked(from, value)?;
+            let events = self.data.transfer(from, to, value)?;
+            //Reverts with error `SafeTransferCheckFailed` if the recipient is a contract and rejected the transfer.
+            self.do_safe_transfer_check(from, to, value, data)?;
+            self.emit_events(events)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance, data: Vec<u8>) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the sender or the recipient is restricted.
+            self.ensure_compliant(Some(from), Some(to))?;
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the spender is restricted.
+            self.ensure_compliant(Some(caller), None)?;
+            //Reverts with error `BalanceLocked` if the tokens are held but still locked.
+            self.ensure_unlocked(from, value)?;
+            let events = self.data.transfer_from(caller, from, to, value)?;
+            //Reverts with error `SafeTransferCheckFailed` if the recipient is a contract and rejected the transfer.
+            self.do_safe_transfer_check(from, to, value, data)?;
+            self.emit_events(events)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            let owner = self.env().caller();
+            let events = self.data.approve(owner, spender, value)?;
+            self.emit_events(events)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn increase_allowance(&mut self, spender: AccountId, added_value: Balance) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            let owner = self.env().caller();
+            let events = self.data.increase_allowance(owner, spender, added_value)?;
+            self.emit_events(events)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn decrease_allowance(&mut self, spender: AccountId, subtracted_value: Balance) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            let owner = self.env().caller();
+            let events = self.data.decrease_allowance(owner, spender, subtracted_value)?;
+            self.emit_events(events)?;
+            Ok(())
+        }
+    }
+
+    impl PSP22Metadata for Psp22Ink{
+        #[ink(message)]
+        fn token_name(&self) -> Option<String> {
+            self.name.clone()
+        }
+
+        #[ink(message)]
+        fn token_symbol(&self) -> Option<String> {
+            self.symbol.clone()
+        }
+
+        #[ink(message)]
+        fn token_decimals(&self) -> u8 {
+            self.decimals
+        }
+    }
+
+    impl PSP22Mintable for Psp22Ink{
+        #[ink(message)]
+        fn mint(&mut self, to: AccountId, value: Balance) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            //Reverts with error `Custom` if the caller does not have the `MINTER` role.
+            self.ensure_role(MINTER, self.env().caller())?;
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the sender or the recipient is restricted.
+            self.ensure_compliant(None, Some(to))?;
+            //Reverts with error `CapExceeded` if the new total supply would be above the cap.
+            if matches!(self.data.total_supply().checked_add(value), Some(total_supply) if total_supply > self.cap) {
+                return Err(PSP22Error::CapExceeded);
+            }
+            let events = self.data.mint(to, value)?;
+            self.emit_events(events)?;
+            Ok(())
+        }
+    }
+
+    impl PSP22Capped for Psp22Ink{
+        #[ink(message)]
+        fn cap(&self) -> Balance {
+            self.cap
+        }
+    }
+
+    impl PSP22Burnable for Psp22Ink{
+        #[ink(message)]
+        fn burn(&mut self, value: Balance) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            let account = self.env().caller();
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the sender or the recipient is restricted.
+            self.ensure_compliant(Some(account), None)?;
+            //Reverts with error `BalanceLocked` if the tokens are held but still locked.
+            self.ensure_unlocked(account, value)?;
+            let events = self.data.burn(account, value)?;
+            self.emit_events(events)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn burn_from(&mut self, account: AccountId, value: Balance) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the sender or the recipient is restricted.
+            self.ensure_compliant(Some(account), None)?;
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the spender is restricted.
+            self.ensure_compliant(Some(caller), None)?;
+            //Reverts with error `BalanceLocked` if the tokens are held but still locked.
+            self.ensure_unlocked(account, value)?;
+            let events = self.data.burn_from(caller, account, value)?;
+            self.emit_events(events)?;
+            Ok(())
+        }
+    }
+
+    impl Ownable for Psp22Ink{
+        #[ink(message)]
+        fn owner(&self) -> Option<AccountId> {
+            self.owner
+        }
+
+        #[ink(message)]
+        fn transfer_ownership(&mut self, new_owner: AccountId) -> core::result::Result<(), OwnableError> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(OwnableError::FlashLoanInProgress)?;
+            self.only_owner()?;
+            //Reverts with error `NewOwnerIsZero` if the new owner's address is zero.
+            if new_owner == AccountId::from([0x0; 32]) {
+                return Err(OwnableError::NewOwnerIsZero);
+            }
+            self.set_owner(Some(new_owner));
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn renounce_ownership(&mut self) -> core::result::Result<(), OwnableError> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(OwnableError::FlashLoanInProgress)?;
+            self.only_owner()?;
+            //Leaves the contract without an owner, disabling all owner-only functionality.
+            self.set_owner(None);
+            Ok(())
+        }
+    }
+
+    impl AccessControl for Psp22Ink{
+        #[ink(message)]
+        fn has_role(&self, role: RoleType, address: AccountId) -> bool {
+            self.roles.contains((role, address))
+        }
+
+        #[ink(message)]
+        fn get_role_admin(&self, role: RoleType) -> RoleType {
+            self.role_admins.get(role).unwrap_or(DEFAULT_ADMIN_ROLE)
+        }
+
+        #[ink(message)]
+        fn grant_role(&mut self, role: RoleType, account: AccountId) -> core::result::Result<(), AccessControlError> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(AccessControlError::FlashLoanInProgress)?;
+            //Reverts with error `MissingRole` if the caller does not have the admin role of `role`.
+            self.ensure_role(self.get_role_admin(role), self.env().caller())?;
+            //Reverts with error `RoleRedundant` if `account` already has `role`.
+            if self.has_role(role, account) {
+                return Err(AccessControlError::RoleRedundant);
+            }
+            self.do_grant_role(role, account, Some(self.env().caller()));
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn revoke_role(&mut self, role: RoleType, account: AccountId) -> core::result::Result<(), AccessControlError> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(AccessControlError::FlashLoanInProgress)?;
+            //Reverts with error `MissingRole` if the caller does not have the admin role of `role`.
+            self.ensure_role(self.get_role_admin(role), self.env().caller())?;
+            //Reverts with error `MissingRole` if `account` does not have `role`.
+            self.ensure_role(role, account)?;
+            self.do_revoke_role(role, account);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn renounce_role(&mut self, role: RoleType, account: AccountId) -> core::result::Result<(), AccessControlError> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(AccessControlError::FlashLoanInProgress)?;
+            //Reverts with error `InvalidCaller` if the caller tries to renounce a role of another account.
+            if account != self.env().caller() {
+                return Err(AccessControlError::InvalidCaller);
+            }
+            //Reverts with error `MissingRole` if `account` does not have `role`.
+            self.ensure_role(role, account)?;
+            self.do_revoke_role(role, account);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn set_role_admin(&mut self, role: RoleType, admin_role: RoleType) -> core::result::Result<(), AccessControlError> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(AccessControlError::FlashLoanInProgress)?;
+            let previous_admin_role = self.get_role_admin(role);
+            //Reverts with error `MissingRole` if the caller does not have the current admin role of `role`.
+            self.ensure_role(previous_admin_role, self.env().caller())?;
+            self.role_admins.insert(role, &admin_role);
+            self.env().emit_event(RoleAdminChanged {
+                role,
+                previous_admin_role,
+                new_admin_role: admin_role,
+            });
+            Ok(())
+        }
+    }
+
+    impl Pausable for Psp22Ink{
+        #[ink(message)]
+        fn paused(&self) -> bool {
+            self.paused
+        }
+
+        #[ink(message)]
+        fn pause(&mut self) -> Result<()> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(PSP22Error::FlashLoanInProgress)?;
+            //Reverts with error `Custom` if the caller does not have the `PAUSER` role.
+            self.ensure_role(PAUSER, self.env().caller())?;
+            //Reverts with error `Paused` if the contract is already paused.
+            self.ensure_not_paused()?;
+            self.paused = true;
+            //Emits a `Paused` event.
+            self.env().emit_event(Paused {
+                account: self.env().caller(),
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn unpause(&mut self) -> Result<()> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(PSP22Error::FlashLoanInProgress)?;
+            //Reverts with error `Custom` if the caller does not have the `PAUSER` role.
+            self.ensure_role(PAUSER, self.env().caller())?;
+            //Reverts with error `NotPaused` if the contract is not paused.
+            if !self.paused {
+                return Err(PSP22Error::NotPaused);
+            }
+            self.paused = false;
+            //Emits an `Unpaused` event.
+            self.env().emit_event(Unpaused {
+                account: self.env().caller(),
+            });
+            Ok(())
+        }
+    }
+
+    impl PSP22Permit for Psp22Ink{
+        #[ink(message)]
+        fn permit(&mut self, owner: AccountId, spender: AccountId, value: Balance, deadline: Timestamp, signature: [u8; 65]) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            //Reverts with error `PermitExpired` if the deadline has passed.
+            if self.env().block_timestamp() > deadline {
+                return Err(PSP22Error::PermitExpired);
+            }
+            //Reverts with error `PermitInvalidSignature` if the signature was not made by `owner` over the current nonce.
+            let nonce = self.nonces(owner);
+            let hash = self.permit_hash(owner, spender, value, nonce, deadline);
+            let public_key = self
+                .env()
+                .ecdsa_recover(&signature, &hash)
+                .map_err(|_| PSP22Error::PermitInvalidSignature)?;
+            let signer = AccountId::from(self.env().hash_bytes::<Blake2x256>(&public_key));
+            if signer != owner {
+                return Err(PSP22Error::PermitInvalidSignature);
+            }
+            //Consumes the nonce so that the signature cannot be used again.
+            let next_nonce = nonce.checked_add(1).ok_or(PSP22Error::Overflow)?;
+            self.nonces.insert(owner, &next_nonce);
+            let events = self.data.approve(owner, spender, value)?;
+            self.emit_events(events)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn nonces(&self, owner: AccountId) -> u64 {
+            self.nonces.get(owner).unwrap_or_default()
+        }
+    }
+
+    impl Compliance for Psp22Ink{
+        #[ink(message)]
+        fn is_blacklisted(&self, account: AccountId) -> bool {
+            self.blacklist.contains(account)
+        }
+
+        #[ink(message)]
+        fn add_to_blacklist(&mut self, account: AccountId) -> Result<()> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(PSP22Error::FlashLoanInProgress)?;
+            //Reverts with error `Custom` if the caller does not have the `COMPLIANCE` role.
+            self.ensure_role(COMPLIANCE, self.env().caller())?;
+            self.blacklist.insert(account, &());
+            self.env().emit_event(BlacklistUpdated {
+                account,
+                blacklisted: true,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn remove_from_blacklist(&mut self, account: AccountId) -> Result<()> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(PSP22Error::FlashLoanInProgress)?;
+            //Reverts with error `Custom` if the caller does not have the `COMPLIANCE` role.
+            self.ensure_role(COMPLIANCE, self.env().caller())?;
+            self.blacklist.remove(account);
+            self.env().emit_event(BlacklistUpdated {
+                account,
+                blacklisted: false,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn is_allowlisted(&self, account: AccountId) -> bool {
+            self.allowlist.contains(account)
+        }
+
+        #[ink(message)]
+        fn add_to_allowlist(&mut self, account: AccountId) -> Result<()> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(PSP22Error::FlashLoanInProgress)?;
+            //Reverts with error `Custom` if the caller does not have the `COMPLIANCE` role.
+            self.ensure_role(COMPLIANCE, self.env().caller())?;
+            self.allowlist.insert(account, &());
+            self.env().emit_event(AllowlistUpdated {
+                account,
+                allowlisted: true,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn remove_from_allowlist(&mut self, account: AccountId) -> Result<()> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(PSP22Error::FlashLoanInProgress)?;
+            //Reverts with error `Custom` if the caller does not have the `COMPLIANCE` role.
+            self.ensure_role(COMPLIANCE, self.env().caller())?;
+            self.allowlist.remove(account);
+            self.env().emit_event(AllowlistUpdated {
+                account,
+                allowlisted: false,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn allowlist_only(&self) -> bool {
+            self.allowlist_only
+        }
+
+        #[ink(message)]
+        fn set_allowlist_only(&mut self, enabled: bool) -> Result<()> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(PSP22Error::FlashLoanInProgress)?;
+            //Reverts with error `Custom` if the caller does not have the `COMPLIANCE` role.
+            self.ensure_role(COMPLIANCE, self.env().caller())?;
+            self.allowlist_only = enabled;
+            self.env().emit_event(AllowlistOnlyUpdated { enabled });
+            Ok(())
+        }
+    }
+
+    impl PSP22Snapshot for Psp22Ink{
+        #[ink(message)]
+        fn balance_of_at(&self, account: AccountId, block: BlockNumber) -> Balance {
+            let count = self.balance_checkpoint_counts.get(account).unwrap_or_default();
+            Self::upper_lookup(count, block, |index| self.balance_checkpoints.get((account, index)))
+        }
+
+        #[ink(message)]
+        fn total_supply_at(&self, block: BlockNumber) -> Balance {
+            Self::upper_lookup(self.supply_checkpoint_count, block, |index| self.supply_checkpoints.get(index))
+        }
+    }
+
+    impl PSP22Votes for Psp22Ink{
+        #[ink(message)]
+        fn delegate(&mut self, delegatee: AccountId) -> Result<()> {
+            let delegator = self.env().caller();
+            let from_delegate = self.delegates.get(delegator);
+            self.delegates.insert(delegator, &delegatee);
+            self.env().emit_event(DelegateChanged {
+                delegator,
+                from_delegate,
+                to_delegate: delegatee,
+            });
+            self.move_voting_power(from_delegate, Some(delegatee), self.data.balance_of(delegator))
+        }
+
+        #[ink(message)]
+        fn delegates(&self, account: AccountId) -> Option<AccountId> {
+            self.delegates.get(account)
+        }
+
+        #[ink(message)]
+        fn get_votes(&self, account: AccountId) -> Balance {
+            let count = self.vote_checkpoint_counts.get(account).unwrap_or_default();
+            count
+                .checked_sub(1)
+                .and_then(|last| self.vote_checkpoints.get((account, last)))
+                .map(|(_, votes)| votes)
+                .unwrap_or_default()
+        }
+
+        #[ink(message)]
+        fn get_past_votes(&self, account: AccountId, block: BlockNumber) -> Balance {
+            let count = self.vote_checkpoint_counts.get(account).unwrap_or_default();
+            Self::upper_lookup(count, block, |index| self.vote_checkpoints.get((account, index)))
+        }
+    }
+
+    impl PSP22FlashLender for Psp22Ink{
+        #[ink(message)]
+        fn max_flash_loan(&self) -> Balance {
+            self.cap.saturating_sub(self.data.total_supply())
+        }
+
+        #[ink(message)]
+        fn flash_fee(&self, amount: Balance) -> Balance {
+            //Splits `amount` at 10_000 so that the exact fee is computed without overflowing.
+            amount / 10_000 * FLASH_FEE_BPS + amount % 10_000 * FLASH_FEE_BPS / 10_000
+        }
+
+        #[ink(message)]
+        fn flash_loan(&mut self, receiver: AccountId, amount: Balance, data: Vec<u8>) -> Result<()> {
+            //Reverts with error `FlashLoanInProgress` if called back by a flash loan borrower.
+            self.ensure_no_flash_loan(PSP22Error::FlashLoanInProgress)?;
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the receiver is restricted.
+            self.ensure_compliant(None, Some(receiver))?;
+            //Reverts with error `CapExceeded` if the loan would raise the total supply above the cap.
+            if amount > self.max_flash_loan() {
+                return Err(PSP22Error::CapExceeded);
+            }
+            let fee = self.flash_fee(amount);
+            //Reverts with error `Overflow` if the repayment does not fit into `u128`.
+            let repayment = amount.checked_add(fee).ok_or(PSP22Error::Overflow)?;
+            let events = self.data.mint(receiver, amount)?;
+            self.emit_events(events)?;
+            //The borrower calls back into this contract, which loads the root storage cell and writes it back on return.
+            //The state is flushed before the call and reloaded after it so that neither side overwrites the other.
+            self.flash_loan_lock.set(&true);
+            ink::env::set_contract_storage(&<Self as StorageKey>::KEY, self);
+            let callback_result = self.do_flash_loan_callback(receiver, amount, fee, data);
+            let reloaded = ink::env::get_contract_storage::<_, Self>(&<Self as StorageKey>::KEY);
+            self.flash_loan_lock.set(&false);
+            //Reverts with error `Custom("StorageReloadFailed")` if the root storage cell cannot be read back.
+            *self = reloaded
+                .ok()
+                .flatten()
+                .ok_or_else(|| PSP22Error::Custom("StorageReloadFailed".to_string()))?;
+            //Reverts with error `FlashLoanRejected` if the receiver does not accept the loan.
+            callback_result?;
+            //Reverts with error `BalanceLocked` if the repayment would take time-locked tokens of the receiver.
+            self.ensure_unlocked(receiver, repayment)?;
+            //Reverts with error `FlashLoanRepaymentFailed` if the receiver did not allow this contract
+            //to take back the loan and the fee, or does not hold enough tokens.
+            let this = self.env().account_id();
+            let events = self
+                .data
+                .burn_from(this, receiver, repayment)
+                .map_err(|_| PSP22Error::FlashLoanRepaymentFailed)?;
+            self.emit_events(events)?;
+            Ok(())
+        }
+    }
+
+    impl PSP22Vesting for Psp22Ink{
+        #[ink(message)]
+        fn create_vesting_schedule(
+            &mut self,
+            beneficiary: AccountId,
+            amount: Balance,
+            start: Timestamp,
+            cliff: Timestamp,
+            duration: Timestamp,
+        ) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            //Reverts with error `Custom` if the caller is not the owner.
+            self.only_owner()?;
+            //Reverts with error `InvalidVestingSchedule` if the amount or the duration is zero,
+            //or if the duration is shorter than the cliff.
+            if amount == 0 || duration == 0 || cliff > duration {
+                return Err(PSP22Error::InvalidVestingSchedule);
+            }
+            //Reverts with error `VestingScheduleExists` if the beneficiary has a schedule that is not fully released.
+            //A fully released schedule is replaced.
+            if matches!(self.vesting_schedules.get(beneficiary), Some(schedule) if schedule.released < schedule.total) {
+                return Err(PSP22Error::VestingScheduleExists);
+            }
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the owner or the beneficiary is restricted.
+            let owner = self.env().caller();
+            self.ensure_compliant(Some(owner), Some(beneficiary))?;
+            //Reverts with error `BalanceLocked` if the tokens are held but still locked.
+            self.ensure_unlocked(owner, amount)?;
+            let events = self.data.transfer(owner, self.env().account_id(), amount)?;
+            self.vesting_schedules.insert(
+                beneficiary,
+                &VestingSchedule {
+                    total: amount,
+                    released: 0,
+                    start,
+                    cliff,
+                    duration,
+                },
+            );
+            self.emit_events(events)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn vesting_schedule(&self, beneficiary: AccountId) -> Option<VestingSchedule> {
+            self.vesting_schedules.get(beneficiary)
+        }
+
+        #[ink(message)]
+        fn vested_amount(&self, beneficiary: AccountId) -> Balance {
+            self.vesting_schedules
+                .get(beneficiary)
+                .map(|schedule| self.vested_at_now(&schedule))
+                .unwrap_or_default()
+        }
+
+        #[ink(message)]
+        fn releasable(&self, beneficiary: AccountId) -> Balance {
+            self.vesting_schedules
+                .get(beneficiary)
+                .map(|schedule| self.vested_at_now(&schedule).saturating_sub(schedule.released))
+                .unwrap_or_default()
+        }
+
+        #[ink(message)]
+        fn release(&mut self, beneficiary: AccountId) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the beneficiary is restricted.
+            self.ensure_compliant(None, Some(beneficiary))?;
+            let mut schedule = match self.vesting_schedules.get(beneficiary) {
+                Some(schedule) => schedule,
+                None => return Ok(()),
+            };
+            let amount = self.vested_at_now(&schedule).saturating_sub(schedule.released);
+            if amount == 0 {
+                return Ok(());
+            }
+            let events = self.data.transfer(self.env().account_id(), beneficiary, amount)?;
+            schedule.released += amount;
+            self.vesting_schedules.insert(beneficiary, &schedule);
+            self.emit_events(events)?;
+            Ok(())
+        }
+    }
+
+    impl PSP22TimeLock for Psp22Ink{
+        #[ink(message)]
+        fn transfer_locked(&mut self, to: AccountId, value: Balance, unlock_at: Timestamp) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            let from = self.env().caller();
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the sender or the recipient is restricted.
+            self.ensure_compliant(Some(from), Some(to))?;
+            //Reverts with error `ZeroLockValue` if no tokens would be locked.
+            if value == 0 {
+                return Err(PSP22Error::ZeroLockValue);
+            }
+            //Reverts with error `BalanceLocked` if the tokens are held but still locked.
+            self.ensure_unlocked(from, value)?;
+            let now = self.env().block_timestamp();
+            //Reverts with error `LockTooLong` if the tokens would stay locked longer than `MAX_LOCK_DURATION`,
+            //so that lock slots filled by other accounts always free up again.
+            if unlock_at > now.saturating_add(MAX_LOCK_DURATION) {
+                return Err(PSP22Error::LockTooLong);
+            }
+            let mut locks = self.locks.get(to).unwrap_or_default();
+            if unlock_at > now {
+                //Expired locks are dropped and locks ending together are merged, so that the list only grows
+                //with distinct active unlock times.
+                locks.retain(|&(lock_end, _)| lock_end > now);
+                match locks.iter_mut().find(|(lock_end, _)| *lock_end == unlock_at) {
+                    Some((_, locked)) => {
+                        //Reverts with error `Overflow` if the merged lock does not fit into `u128`.
+                        *locked = locked.checked_add(value).ok_or(PSP22Error::Overflow)?;
+                    }
+                    //Reverts with error `TooManyLocks` if `to` already has the maximum number of active locks.
+                    None if locks.len() >= MAX_LOCKS_PER_ACCOUNT => return Err(PSP22Error::TooManyLocks),
+                    None => locks.push((unlock_at, value)),
+                }
+            }
+            let events = self.data.transfer(from, to, value)?;
+            if unlock_at > now {
+                self.locks.insert(to, &locks);
+            }
+            self.emit_events(events)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn locked_balance_of(&self, account: AccountId) -> Balance {
+            let now = self.env().block_timestamp();
+            self.locks
+                .get(account)
+                .unwrap_or_default()
+                .iter()
+                .filter(|&&(unlock_at, _)| unlock_at > now)
+                .fold(0, |locked: Balance, &(_, value)| locked.saturating_add(value))
+        }
+
+        #[ink(message)]
+        fn unlocked_balance_of(&self, account: AccountId) -> Balance {
+            self.data.balance_of(account).saturating_sub(self.locked_balance_of(account))
+        }
+    }
+
+    impl PSP22Batch for Psp22Ink{
+        #[ink(message)]
+        fn batch_transfer(&mut self, recipients: Vec<(AccountId, Balance)>) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            let from = self.env().caller();
+            self.do_batch_transfer(from, recipients, None)
+        }
+
+        #[ink(message)]
+        fn batch_transfer_from(&mut self, from: AccountId, recipients: Vec<(AccountId, Balance)>) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            self.do_batch_transfer(from, recipients, Some(caller))
+        }
+    }
+
+    impl PSP22Airdrop for Psp22Ink{
+        #[ink(message)]
+        fn set_merkle_root(&mut self, merkle_root: [u8; 32], amount: Balance) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            //Reverts with error `Custom` if the caller is not the owner.
+            self.only_owner()?;
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the owner is restricted.
+            let owner = self.env().caller();
+            self.ensure_compliant(Some(owner), None)?;
+            //Reverts with error `Overflow` if all round numbers are used up.
+            let airdrop_round = self.airdrop_round.checked_add(1).ok_or(PSP22Error::Overflow)?;
+            let this = self.env().account_id();
+            if self.airdrop_pool > 0 {
+                let events = self.data.transfer(this, owner, self.airdrop_pool)?;
+                self.airdrop_pool = 0;
+                self.emit_events(events)?;
+            }
+            //Reverts with error `BalanceLocked` if the tokens are held but still locked.
+            self.ensure_unlocked(owner, amount)?;
+            let events = self.data.transfer(owner, this, amount)?;
+            self.merkle_root = Some(merkle_root);
+            self.airdrop_round = airdrop_round;
+            self.airdrop_pool = amount;
+            self.emit_events(events)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn merkle_root(&self) -> Option<[u8; 32]> {
+            self.merkle_root
+        }
+
+        #[ink(message)]
+        fn airdrop_pool(&self) -> Balance {
+            self.airdrop_pool
+        }
+
+        #[ink(message)]
+        fn is_claimed(&self, index: u32) -> bool {
+            let word = self.claimed_bitmap.get((self.airdrop_round, index / 128)).unwrap_or_default();
+            word & (1 << (index % 128)) != 0
+        }
+
+        #[ink(message)]
+        fn claim(&mut self, index: u32, account: AccountId, amount: Balance, proof: Vec<[u8; 32]>) -> Result<()> {
+            //Reverts with error `Paused` if the contract is paused.
+            self.ensure_not_paused()?;
+            //Reverts with error `AirdropAlreadyClaimed` if the claim at `index` was already made.
+            if self.is_claimed(index) {
+                return Err(PSP22Error::AirdropAlreadyClaimed);
+            }
+            //Reverts with error `InvalidMerkleProof` if no root is set or the proof does not lead to the root.
+            let computed_root = proof
+                .into_iter()
+                .fold(self.merkle_leaf(index, account, amount), |node, sibling| self.merkle_parent(node, sibling));
+            if self.merkle_root != Some(computed_root) {
+                return Err(PSP22Error::InvalidMerkleProof);
+            }
+            //Reverts with error `Blacklisted` or `NotAllowlisted` if the account is restricted.
+            self.ensure_compliant(None, Some(account))?;
+            //Reverts with error `InsufficientBalance` if the pool does not hold the claimed amount.
+            let airdrop_pool = self.airdrop_pool.checked_sub(amount).ok_or(PSP22Error::InsufficientBalance)?;
+            let events = self.data.transfer(self.env().account_id(), account, amount)?;
+            self.airdrop_pool = airdrop_pool;
+            let word = self.claimed_bitmap.get((self.airdrop_round, index / 128)).unwrap_or_default();
+            self.claimed_bitmap.insert((self.airdrop_round, index / 128), &(word | (1 << (index % 128))));
+            self.emit_events(events)?;
+            self.env().emit_event(Claimed { account, index, amount });
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn default_accounts() -> ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment> {
+            ink::env::test::default_accounts::<ink::env::DefaultEnvironment>()
+        }
+
+        fn set_caller(caller: AccountId) {
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(caller);
+        }
+
+        type Event = <Psp22Ink as ::ink::reflect::ContractEventBase>::Type;
+
+        fn recorded_events() -> Vec<Event> {
+            ink::env::test::recorded_events()
+                .map(|event| {
+                    <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer")
+                })
+                .collect()
+        }
+
+        fn last_event() -> Event {
+            recorded_events().pop().expect("no event was emitted")
+        }
+
+        fn advance_block() {
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+        }
+
+        fn set_block_timestamp(timestamp: Timestamp) {
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(timestamp);
+        }
+
+        fn assert_transfer_event(event: Event, expected_from: Option<AccountId>, expected_to: Option<AccountId>, expected_value: Balance) {
+            if let Event::Transfer(Transfer { from, to, value }) = event {
+                assert_eq!(from, expected_from, "encountered invalid Transfer.from");
+                assert_eq!(to, expected_to, "encountered invalid Transfer.to");
+                assert_eq!(value, expected_value, "encountered invalid Transfer.value");
+            } else {
+                panic!("encountered unexpected event kind: expected a Transfer event")
+            }
+        }
+
+        fn assert_approval_event(event: Event, expected_owner: AccountId, expected_spender: AccountId, expected_value: Balance) {
+            if let Event::Approval(Approval { owner, spender, value }) = event {
+                assert_eq!(owner, expected_owner, "encountered invalid Approval.owner");
+                assert_eq!(spender, expected_spender, "encountered invalid Approval.spender");
+                assert_eq!(value, expected_value, "encountered invalid Approval.value");
+            } else {
+                panic!("encountered unexpected event kind: expected an Approval event")
+            }
+        }
+
+        #[ink::test]
+        fn new_works() {
+            let accounts = default_accounts();
+            let psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.total_supply(), 100);
+            assert_eq!(psp22.balance_of(accounts.alice), 100);
+            assert_eq!(psp22.balance_of(accounts.bob), 0);
+            assert_eq!(psp22.token_name(), None);
+            assert_eq!(psp22.token_symbol(), None);
+            assert_eq!(psp22.token_decimals(), 0);
+            let events = recorded_events();
+            assert_eq!(events.len(), 6);
+            assert_transfer_event(events.into_iter().next().unwrap(), None, Some(accounts.alice), 100);
+        }
+
+        #[ink::test]
+        fn new_with_metadata_works() {
+            let psp22 = Psp22Ink::new_with_metadata(100, Some("Token".to_string()), Some("TKN".to_string()), 18);
+
+            assert_eq!(psp22.total_supply(), 100);
+            assert_eq!(psp22.token_name(), Some("Token".to_string()));
+            assert_eq!(psp22.token_symbol(), Some("TKN".to_string()));
+            assert_eq!(psp22.token_decimals(), 18);
+        }
+
+        #[ink::test]
+        fn transfer_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.transfer(accounts.bob, 10, Vec::new()), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.alice), 90);
+            assert_eq!(psp22.balance_of(accounts.bob), 10);
+            assert_eq!(psp22.total_supply(), 100);
+            assert_transfer_event(last_event(), Some(accounts.alice), Some(accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn transfer_to_self_keeps_balance() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.transfer(accounts.alice, 10, Vec::new()), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.alice), 100);
+            assert_transfer_event(last_event(), Some(accounts.alice), Some(accounts.alice), 10);
+        }
+
+        #[ink::test]
+        fn transfer_with_insufficient_balance_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let events_before = recorded_events().len();
+
+            assert_eq!(psp22.transfer(accounts.bob, 101, Vec::new()), Err(PSP22Error::InsufficientBalance));
+            assert_eq!(psp22.balance_of(accounts.alice), 100);
+            assert_eq!(psp22.balance_of(accounts.bob), 0);
+            assert_eq!(recorded_events().len(), events_before);
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.transfer(accounts.alice, 1, Vec::new()), Err(PSP22Error::InsufficientBalance));
+        }
+
+        #[ink::test]
+        fn transfer_to_zero_address_fails() {
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.transfer(AccountId::from([0x0; 32]), 10, Vec::new()), Err(PSP22Error::ZeroRecipientAddress));
+            assert_eq!(psp22.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn transfer_by_zero_address_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            set_caller(AccountId::from([0x0; 32]));
+            assert_eq!(psp22.transfer(accounts.bob, 0, Vec::new()), Err(PSP22Error::ZeroSenderAddress));
+        }
+
+        #[ink::test]
+        fn transfer_from_zero_address_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let zero_address = AccountId::from([0x0; 32]);
+            set_caller(zero_address);
+            assert_eq!(psp22.approve(accounts.bob, 10), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                psp22.transfer_from(zero_address, accounts.charlie, 0, Vec::new()),
+                Err(PSP22Error::ZeroSenderAddress)
+            );
+            assert_eq!(psp22.allowance(zero_address, accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn safe_transfer_check_maps_receiver_results() {
+            use crate::receiver::receiver_check_result;
+            let not_implemented = Err(PSP22Error::SafeTransferCheckFailed(
+                "Recipient is a contract and does not implement PSP22Receiver".to_string(),
+            ));
+
+            assert_eq!(receiver_check_result(Ok(Ok(Ok(())))), Ok(()));
+            assert_eq!(
+                receiver_check_result(Ok(Ok(Err(PSP22ReceiverError::TransferRejected("Not now".to_string()))))),
+                Err(PSP22Error::SafeTransferCheckFailed("Not now".to_string()))
+            );
+            //The callee exists but does not implement `PSP22Receiver`, or is not callable at all.
+            assert_eq!(receiver_check_result(Ok(Err(ink::LangError::CouldNotReadInput))), not_implemented);
+            assert_eq!(receiver_check_result(Err(ink::env::Error::CalleeTrapped)), not_implemented);
+            assert_eq!(receiver_check_result(Err(ink::env::Error::NotCallable)), not_implemented);
+        }
+
+        #[ink::test]
+        fn approve_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.approve(accounts.bob, 10), Ok(()));
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 10);
+            assert_eq!(psp22.allowance(accounts.bob, accounts.alice), 0);
+            assert_approval_event(last_event(), accounts.alice, accounts.bob, 10);
+
+            //A new approval overwrites the previous one.
+            assert_eq!(psp22.approve(accounts.bob, 3), Ok(()));
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 3);
+            assert_approval_event(last_event(), accounts.alice, accounts.bob, 3);
+        }
+
+        #[ink::test]
+        fn transfer_from_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.approve(accounts.bob, 10), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.transfer_from(accounts.alice, accounts.charlie, 4, Vec::new()), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.alice), 96);
+            assert_eq!(psp22.balance_of(accounts.charlie), 4);
+            assert_eq!(psp22.balance_of(accounts.bob), 0);
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 6);
+            assert_transfer_event(last_event(), Some(accounts.alice), Some(accounts.charlie), 4);
+        }
+
+        #[ink::test]
+        fn transfer_from_spends_allowance_exactly() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.approve(accounts.bob, 10), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.transfer_from(accounts.alice, accounts.bob, 10, Vec::new()), Ok(()));
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 0);
+            assert_eq!(psp22.transfer_from(accounts.alice, accounts.bob, 1, Vec::new()), Err(PSP22Error::InsufficientAllowance));
+        }
+
+        #[ink::test]
+        fn transfer_from_with_insufficient_allowance_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.approve(accounts.bob, 10), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.transfer_from(accounts.alice, accounts.bob, 11, Vec::new()), Err(PSP22Error::InsufficientAllowance));
+            //Allowances are per spender.
+            set_caller(accounts.charlie);
+            assert_eq!(psp22.transfer_from(accounts.alice, accounts.charlie, 1, Vec::new()), Err(PSP22Error::InsufficientAllowance));
+            assert_eq!(psp22.balance_of(accounts.alice), 100);
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn transfer_from_with_insufficient_balance_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.approve(accounts.bob, 1000), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.transfer_from(accounts.alice, accounts.bob, 101, Vec::new()), Err(PSP22Error::InsufficientBalance));
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 1000);
+        }
+
+        #[ink::test]
+        fn transfer_from_to_zero_address_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.approve(accounts.bob, 10), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.transfer_from(accounts.alice, AccountId::from([0x0; 32]), 1, Vec::new()), Err(PSP22Error::ZeroRecipientAddress));
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn increase_allowance_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.increase_allowance(accounts.bob, 10), Ok(()));
+            assert_approval_event(last_event(), accounts.alice, accounts.bob, 10);
+            assert_eq!(psp22.increase_allowance(accounts.bob, 5), Ok(()));
+            assert_approval_event(last_event(), accounts.alice, accounts.bob, 15);
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 15);
+        }
+
+        #[ink::test]
+        fn increase_allowance_for_zero_address_fails() {
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.increase_allowance(AccountId::from([0x0; 32]), 10), Err(PSP22Error::ZeroRecipientAddress));
+        }
+
+        #[ink::test]
+        fn decrease_allowance_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.approve(accounts.bob, 10), Ok(()));
+
+            assert_eq!(psp22.decrease_allowance(accounts.bob, 4), Ok(()));
+            assert_approval_event(last_event(), accounts.alice, accounts.bob, 6);
+            assert_eq!(psp22.decrease_allowance(accounts.bob, 6), Ok(()));
+            assert_approval_event(last_event(), accounts.alice, accounts.bob, 0);
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 0);
+            assert_eq!(psp22.decrease_allowance(accounts.bob, 1), Err(PSP22Error::InsufficientAllowance));
+        }
+
+        #[ink::test]
+        fn decrease_allowance_for_zero_address_fails() {
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.decrease_allowance(AccountId::from([0x0; 32]), 0), Err(PSP22Error::ZeroRecipientAddress));
+        }
+
+        #[ink::test]
+        fn mint_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.mint(accounts.bob, 50), Ok(()));
+            assert_eq!(psp22.total_supply(), 150);
+            assert_eq!(psp22.balance_of(accounts.bob), 50);
+            assert_transfer_event(last_event(), None, Some(accounts.bob), 50);
+        }
+
+        #[ink::test]
+        fn mint_to_zero_address_fails() {
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.mint(AccountId::from([0x0; 32]), 50), Err(PSP22Error::ZeroRecipientAddress));
+            assert_eq!(psp22.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn burn_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.burn(30), Ok(()));
+            assert_eq!(psp22.total_supply(), 70);
+            assert_eq!(psp22.balance_of(accounts.alice), 70);
+            assert_transfer_event(last_event(), Some(accounts.alice), None, 30);
+            assert_eq!(psp22.burn(71), Err(PSP22Error::InsufficientBalance));
+        }
+
+        #[ink::test]
+        fn burn_from_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.approve(accounts.bob, 10), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.burn_from(accounts.alice, 4), Ok(()));
+            assert_eq!(psp22.total_supply(), 96);
+            assert_eq!(psp22.balance_of(accounts.alice), 96);
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 6);
+            assert_transfer_event(last_event(), Some(accounts.alice), None, 4);
+            assert_eq!(psp22.burn_from(accounts.alice, 7), Err(PSP22Error::InsufficientAllowance));
+        }
+
+        #[ink::test]
+        fn burn_from_with_insufficient_balance_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.approve(accounts.bob, 1000), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.burn_from(accounts.alice, 101), Err(PSP22Error::InsufficientBalance));
+            assert_eq!(psp22.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn ownership_transfer_emits_event() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.transfer_ownership(accounts.bob), Ok(()));
+            if let Event::OwnershipTransferred(OwnershipTransferred { previous_owner, new_owner }) = last_event() {
+                assert_eq!(previous_owner, Some(accounts.alice));
+                assert_eq!(new_owner, Some(accounts.bob));
+            } else {
+                panic!("encountered unexpected event kind: expected an OwnershipTransferred event")
+            }
+        }
+
+        #[ink::test]
+        fn grant_role_emits_event() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.grant_role(PAUSER, accounts.bob), Ok(()));
+            if let Event::RoleGranted(RoleGranted { role, grantee, grantor }) = last_event() {
+                assert_eq!(role, PAUSER);
+                assert_eq!(grantee, accounts.bob);
+                assert_eq!(grantor, Some(accounts.alice));
+            } else {
+                panic!("encountered unexpected event kind: expected a RoleGranted event")
+            }
+        }
+
+        #[ink::test]
+        fn pause_and_unpause_emit_events() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.pause(), Ok(()));
+            assert!(matches!(last_event(), Event::Paused(Paused { account }) if account == accounts.alice));
+            assert_eq!(psp22.unpause(), Ok(()));
+            assert!(matches!(last_event(), Event::Unpaused(Unpaused { account }) if account == accounts.alice));
+        }
+
+        #[ink::test]
+        fn transfer_of_max_supply_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(Balance::MAX);
+
+            assert_eq!(psp22.transfer(accounts.bob, Balance::MAX, Vec::new()), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.alice), 0);
+            assert_eq!(psp22.balance_of(accounts.bob), Balance::MAX);
+            assert_eq!(psp22.total_supply(), Balance::MAX);
+        }
+
+        #[ink::test]
+        fn transfer_from_with_max_allowance_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(Balance::MAX);
+            assert_eq!(psp22.approve(accounts.bob, Balance::MAX), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.transfer_from(accounts.alice, accounts.charlie, Balance::MAX, Vec::new()), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.charlie), Balance::MAX);
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn increase_allowance_overflow_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.increase_allowance(accounts.bob, Balance::MAX), Ok(()));
+
+            assert_eq!(psp22.increase_allowance(accounts.bob, 1), Err(PSP22Error::Overflow));
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), Balance::MAX);
+        }
+
+        #[ink::test]
+        fn decrease_allowance_below_zero_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.approve(accounts.bob, 1), Ok(()));
+
+            assert_eq!(psp22.decrease_allowance(accounts.bob, Balance::MAX), Err(PSP22Error::InsufficientAllowance));
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 1);
+        }
+
+        #[ink::test]
+        fn mint_over_max_supply_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(Balance::MAX);
+
+            assert_eq!(psp22.mint(accounts.bob, 1), Err(PSP22Error::Overflow));
+            assert_eq!(psp22.total_supply(), Balance::MAX);
+            assert_eq!(psp22.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn mint_up_to_max_supply_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(1);
+
+            assert_eq!(psp22.mint(accounts.bob, Balance::MAX - 1), Ok(()));
+            assert_eq!(psp22.total_supply(), Balance::MAX);
+            assert_eq!(psp22.balance_of(accounts.bob), Balance::MAX - 1);
+        }
+
+        #[ink::test]
+        fn burn_of_max_supply_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(Balance::MAX);
+
+            assert_eq!(psp22.burn(Balance::MAX), Ok(()));
+            assert_eq!(psp22.total_supply(), 0);
+            assert_eq!(psp22.balance_of(accounts.alice), 0);
+            assert_eq!(psp22.burn(1), Err(PSP22Error::InsufficientBalance));
+        }
+
+        #[ink::test]
+        fn deployer_is_owner() {
+            let accounts = default_accounts();
+            let psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.owner(), Some(accounts.alice));
+        }
+
+        #[ink::test]
+        fn transfer_ownership_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.transfer_ownership(accounts.bob), Ok(()));
+            assert_eq!(psp22.owner(), Some(accounts.bob));
+            assert_eq!(psp22.transfer_ownership(accounts.charlie), Err(OwnableError::CallerIsNotOwner));
+        }
+
+        #[ink::test]
+        fn transfer_ownership_to_zero_fails() {
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.transfer_ownership(AccountId::from([0x0; 32])), Err(OwnableError::NewOwnerIsZero));
+        }
+
+        #[ink::test]
+        fn renounce_ownership_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.renounce_ownership(), Err(OwnableError::CallerIsNotOwner));
+
+            set_caller(accounts.alice);
+            assert_eq!(psp22.renounce_ownership(), Ok(()));
+            assert_eq!(psp22.owner(), None);
+            assert_eq!(psp22.transfer_ownership(accounts.alice), Err(OwnableError::CallerIsNotOwner));
+        }
+
+        #[ink::test]
+        fn deployer_has_admin_and_minter_roles() {
+            let accounts = default_accounts();
+            let psp22 = Psp22Ink::new(100);
+
+            assert!(psp22.has_role(DEFAULT_ADMIN_ROLE, accounts.alice));
+            assert!(psp22.has_role(MINTER, accounts.alice));
+            assert!(!psp22.has_role(MINTER, accounts.bob));
+            assert_eq!(psp22.get_role_admin(MINTER), DEFAULT_ADMIN_ROLE);
+        }
+
+        #[ink::test]
+        fn grant_and_revoke_role_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.grant_role(MINTER, accounts.bob), Ok(()));
+            assert!(psp22.has_role(MINTER, accounts.bob));
+            assert_eq!(psp22.grant_role(MINTER, accounts.bob), Err(AccessControlError::RoleRedundant));
+
+            assert_eq!(psp22.revoke_role(MINTER, accounts.bob), Ok(()));
+            assert!(!psp22.has_role(MINTER, accounts.bob));
+            assert_eq!(psp22.revoke_role(MINTER, accounts.bob), Err(AccessControlError::MissingRole));
+        }
+
+        #[ink::test]
+        fn grant_role_requires_admin_role() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.grant_role(MINTER, accounts.bob), Err(AccessControlError::MissingRole));
+            assert_eq!(psp22.revoke_role(MINTER, accounts.alice), Err(AccessControlError::MissingRole));
+        }
+
+        #[ink::test]
+        fn renounce_role_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.renounce_role(MINTER, accounts.bob), Err(AccessControlError::InvalidCaller));
+            assert_eq!(psp22.renounce_role(MINTER, accounts.alice), Ok(()));
+            assert!(!psp22.has_role(MINTER, accounts.alice));
+            assert_eq!(psp22.renounce_role(MINTER, accounts.alice), Err(AccessControlError::MissingRole));
+        }
+
+        #[ink::test]
+        fn mint_requires_minter_role() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.mint(accounts.bob, 1), Err(PSP22Error::Custom("MissingRole".to_string())));
+
+            set_caller(accounts.alice);
+            assert_eq!(psp22.grant_role(MINTER, accounts.bob), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.mint(accounts.bob, 1), Ok(()));
+            assert_eq!(psp22.total_supply(), 101);
+        }
+
+        #[ink::test]
+        fn pause_blocks_state_changes() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.approve(accounts.bob, 10), Ok(()));
+
+            assert_eq!(psp22.pause(), Ok(()));
+            assert!(psp22.paused());
+            assert_eq!(psp22.transfer(accounts.bob, 1, Vec::new()), Err(PSP22Error::Paused));
+            assert_eq!(psp22.approve(accounts.bob, 1), Err(PSP22Error::Paused));
+            assert_eq!(psp22.increase_allowance(accounts.bob, 1), Err(PSP22Error::Paused));
+            assert_eq!(psp22.decrease_allowance(accounts.bob, 1), Err(PSP22Error::Paused));
+            assert_eq!(psp22.mint(accounts.bob, 1), Err(PSP22Error::Paused));
+            assert_eq!(psp22.burn(1), Err(PSP22Error::Paused));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.transfer_from(accounts.alice, accounts.bob, 1, Vec::new()), Err(PSP22Error::Paused));
+            assert_eq!(psp22.burn_from(accounts.alice, 1), Err(PSP22Error::Paused));
+
+            set_caller(accounts.alice);
+            assert_eq!(psp22.unpause(), Ok(()));
+            assert!(!psp22.paused());
+            assert_eq!(psp22.transfer(accounts.bob, 1, Vec::new()), Ok(()));
+        }
+
+        #[ink::test]
+        fn pause_requires_pauser_role() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.pause(), Err(PSP22Error::Custom("MissingRole".to_string())));
+
+            set_caller(accounts.alice);
+            assert_eq!(psp22.pause(), Ok(()));
+            assert_eq!(psp22.pause(), Err(PSP22Error::Paused));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.unpause(), Err(PSP22Error::Custom("MissingRole".to_string())));
+
+            set_caller(accounts.alice);
+            assert_eq!(psp22.unpause(), Ok(()));
+            assert_eq!(psp22.unpause(), Err(PSP22Error::NotPaused));
+        }
+
+        //Signs the permit payload with `secret_key` and returns the signature together with the signer's account.
+        fn sign_permit(psp22: &Psp22Ink, secret_key: [u8; 32], spender: AccountId, value: Balance, deadline: Timestamp) -> (AccountId, [u8; 65]) {
+            let secp = secp256k1::Secp256k1::new();
+            let secret_key = secp256k1::SecretKey::from_slice(&secret_key).expect("invalid secret key");
+            let public_key = secp256k1::PublicKey::from_secret_key(&secp, &secret_key).serialize();
+            let mut owner = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(&public_key, &mut owner);
+            let owner = AccountId::from(owner);
+
+            let hash = psp22.permit_hash(owner, spender, value, psp22.nonces(owner), deadline);
+            let message = secp256k1::Message::from_slice(&hash).expect("invalid message hash");
+            let (recovery_id, compact) = secp.sign_ecdsa_recoverable(&message, &secret_key).serialize_compact();
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = recovery_id.to_i32() as u8;
+            (owner, signature)
+        }
+
+        #[ink::test]
+        fn permit_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let (owner, signature) = sign_permit(&psp22, [0x11; 32], accounts.bob, 10, 1_000);
+
+            //Anyone can submit the permit.
+            set_caller(accounts.charlie);
+            assert_eq!(psp22.permit(owner, accounts.bob, 10, 1_000, signature), Ok(()));
+            assert_eq!(psp22.allowance(owner, accounts.bob), 10);
+            assert_eq!(psp22.nonces(owner), 1);
+            assert_approval_event(last_event(), owner, accounts.bob, 10);
+        }
+
+        #[ink::test]
+        fn permit_cannot_be_replayed() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let (owner, signature) = sign_permit(&psp22, [0x11; 32], accounts.bob, 10, 1_000);
+            assert_eq!(psp22.permit(owner, accounts.bob, 10, 1_000, signature), Ok(()));
+            assert_eq!(psp22.approve(accounts.bob, 0), Ok(()));
+
+            assert_eq!(psp22.permit(owner, accounts.bob, 10, 1_000, signature), Err(PSP22Error::PermitInvalidSignature));
+            assert_eq!(psp22.nonces(owner), 1);
+        }
+
+        #[ink::test]
+        fn permit_with_wrong_payload_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let (owner, signature) = sign_permit(&psp22, [0x11; 32], accounts.bob, 10, 1_000);
+
+            assert_eq!(psp22.permit(owner, accounts.bob, 11, 1_000, signature), Err(PSP22Error::PermitInvalidSignature));
+            assert_eq!(psp22.permit(owner, accounts.charlie, 10, 1_000, signature), Err(PSP22Error::PermitInvalidSignature));
+            assert_eq!(psp22.permit(accounts.alice, accounts.bob, 10, 1_000, signature), Err(PSP22Error::PermitInvalidSignature));
+            assert_eq!(psp22.allowance(owner, accounts.bob), 0);
+            assert_eq!(psp22.nonces(owner), 0);
+        }
+
+        #[ink::test]
+        fn permit_after_deadline_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let (owner, signature) = sign_permit(&psp22, [0x11; 32], accounts.bob, 10, 1_000);
+
+            set_block_timestamp(1_001);
+            assert_eq!(psp22.permit(owner, accounts.bob, 10, 1_000, signature), Err(PSP22Error::PermitExpired));
+            assert_eq!(psp22.allowance(owner, accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn blacklisted_accounts_cannot_send_or_receive() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.transfer(accounts.bob, 10, Vec::new()), Ok(()));
+
+            assert_eq!(psp22.add_to_blacklist(accounts.bob), Ok(()));
+            assert!(psp22.is_blacklisted(accounts.bob));
+            assert!(matches!(last_event(), Event::BlacklistUpdated(BlacklistUpdated { account, blacklisted: true }) if account == accounts.bob));
+            assert_eq!(psp22.transfer(accounts.bob, 1, Vec::new()), Err(PSP22Error::Blacklisted(accounts.bob)));
+            assert_eq!(psp22.mint(accounts.bob, 1), Err(PSP22Error::Blacklisted(accounts.bob)));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.transfer(accounts.alice, 1, Vec::new()), Err(PSP22Error::Blacklisted(accounts.bob)));
+            assert_eq!(psp22.burn(1), Err(PSP22Error::Blacklisted(accounts.bob)));
+            assert_eq!(psp22.approve(accounts.charlie, 5), Ok(()));
+
+            set_caller(accounts.charlie);
+            assert_eq!(psp22.transfer_from(accounts.bob, accounts.charlie, 1, Vec::new()), Err(PSP22Error::Blacklisted(accounts.bob)));
+
+            set_caller(accounts.alice);
+            assert_eq!(psp22.remove_from_blacklist(accounts.bob), Ok(()));
+            assert!(!psp22.is_blacklisted(accounts.bob));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.transfer(accounts.alice, 1, Vec::new()), Ok(()));
+        }
+
+        #[ink::test]
+        fn allowlist_only_mode_restricts_transfers() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.set_allowlist_only(true), Ok(()));
+            assert!(psp22.allowlist_only());
+            assert_eq!(psp22.transfer(accounts.bob, 1, Vec::new()), Err(PSP22Error::NotAllowlisted(accounts.alice)));
+
+            assert_eq!(psp22.add_to_allowlist(accounts.alice), Ok(()));
+            assert_eq!(psp22.transfer(accounts.bob, 1, Vec::new()), Err(PSP22Error::NotAllowlisted(accounts.bob)));
+
+            assert_eq!(psp22.add_to_allowlist(accounts.bob), Ok(()));
+            assert!(psp22.is_allowlisted(accounts.bob));
+            assert_eq!(psp22.transfer(accounts.bob, 1, Vec::new()), Ok(()));
+
+            assert_eq!(psp22.remove_from_allowlist(accounts.bob), Ok(()));
+            assert_eq!(psp22.transfer(accounts.bob, 1, Vec::new()), Err(PSP22Error::NotAllowlisted(accounts.bob)));
+
+            assert_eq!(psp22.set_allowlist_only(false), Ok(()));
+            assert_eq!(psp22.transfer(accounts.bob, 1, Vec::new()), Ok(()));
+        }
+
+        #[ink::test]
+        fn compliance_lists_require_compliance_role() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.add_to_blacklist(accounts.charlie), Err(PSP22Error::Custom("MissingRole".to_string())));
+            assert_eq!(psp22.add_to_allowlist(accounts.charlie), Err(PSP22Error::Custom("MissingRole".to_string())));
+            assert_eq!(psp22.set_allowlist_only(true), Err(PSP22Error::Custom("MissingRole".to_string())));
+            assert!(!psp22.is_blacklisted(accounts.charlie));
+        }
+
+        #[ink::test]
+        fn new_capped_works() {
+            let accounts = default_accounts();
+            let psp22 = Psp22Ink::new_capped(100, 1000, None, None, 0).unwrap();
+
+            assert_eq!(psp22.cap(), 1000);
+            assert_eq!(psp22.total_supply(), 100);
+            assert_eq!(psp22.balance_of(accounts.alice), 100);
+        }
+
+        #[ink::test]
+        fn new_capped_over_cap_fails() {
+            assert!(matches!(Psp22Ink::new_capped(1001, 1000, None, None, 0), Err(PSP22Error::CapExceeded)));
+        }
+
+        #[ink::test]
+        fn uncapped_token_has_max_cap() {
+            let psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.cap(), Balance::MAX);
+        }
+
+        #[ink::test]
+        fn mint_up_to_cap_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new_capped(100, 1000, None, None, 0).unwrap();
+
+            assert_eq!(psp22.mint(accounts.bob, 900), Ok(()));
+            assert_eq!(psp22.total_supply(), 1000);
+            assert_eq!(psp22.mint(accounts.bob, 1), Err(PSP22Error::CapExceeded));
+            assert_eq!(psp22.total_supply(), 1000);
+            assert_eq!(psp22.balance_of(accounts.bob), 900);
+        }
+
+        #[ink::test]
+        fn mint_after_burn_stays_under_cap() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new_capped(1000, 1000, None, None, 0).unwrap();
+
+            assert_eq!(psp22.burn(10), Ok(()));
+            assert_eq!(psp22.mint(accounts.bob, 11), Err(PSP22Error::CapExceeded));
+            assert_eq!(psp22.mint(accounts.bob, 10), Ok(()));
+            assert_eq!(psp22.total_supply(), 1000);
+        }
+
+        #[ink::test]
+        fn balance_of_at_returns_historical_balances() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            advance_block();
+            assert_eq!(psp22.transfer(accounts.bob, 10, Vec::new()), Ok(()));
+            advance_block();
+            advance_block();
+            assert_eq!(psp22.transfer(accounts.bob, 20, Vec::new()), Ok(()));
+            assert_eq!(psp22.transfer(accounts.bob, 5, Vec::new()), Ok(()));
+
+            assert_eq!(psp22.balance_of_at(accounts.alice, 0), 100);
+            assert_eq!(psp22.balance_of_at(accounts.bob, 0), 0);
+            assert_eq!(psp22.balance_of_at(accounts.alice, 1), 90);
+            assert_eq!(psp22.balance_of_at(accounts.bob, 1), 10);
+            assert_eq!(psp22.balance_of_at(accounts.bob, 2), 10);
+            assert_eq!(psp22.balance_of_at(accounts.bob, 3), 35);
+            assert_eq!(psp22.balance_of_at(accounts.bob, 100), 35);
+            assert_eq!(psp22.balance_of_at(accounts.charlie, 3), 0);
+        }
+
+        #[ink::test]
+        fn total_supply_at_returns_historical_supply() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            advance_block();
+            assert_eq!(psp22.mint(accounts.bob, 50), Ok(()));
+            advance_block();
+            assert_eq!(psp22.burn(30), Ok(()));
+            assert_eq!(psp22.transfer(accounts.bob, 10, Vec::new()), Ok(()));
+
+            assert_eq!(psp22.total_supply_at(0), 100);
+            assert_eq!(psp22.total_supply_at(1), 150);
+            assert_eq!(psp22.total_supply_at(2), 120);
+            assert_eq!(psp22.balance_of_at(accounts.alice, 2), 60);
+            assert_eq!(psp22.balance_of_at(accounts.bob, 1), 50);
+        }
+
+        #[ink::test]
+        fn delegate_moves_voting_power() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.get_votes(accounts.alice), 0);
+
+            assert_eq!(psp22.delegate(accounts.alice), Ok(()));
+            assert_eq!(psp22.delegates(accounts.alice), Some(accounts.alice));
+            assert_eq!(psp22.get_votes(accounts.alice), 100);
+            assert!(matches!(last_event(), Event::DelegateVotesChanged(DelegateVotesChanged { delegate, previous_votes: 0, new_votes: 100 }) if delegate == accounts.alice));
+
+            assert_eq!(psp22.delegate(accounts.bob), Ok(()));
+            assert_eq!(psp22.get_votes(accounts.alice), 0);
+            assert_eq!(psp22.get_votes(accounts.bob), 100);
+        }
+
+        #[ink::test]
+        fn transfers_move_delegated_votes() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.delegate(accounts.alice), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(psp22.delegate(accounts.charlie), Ok(()));
+            set_caller(accounts.alice);
+
+            assert_eq!(psp22.transfer(accounts.bob, 30, Vec::new()), Ok(()));
+            assert_eq!(psp22.get_votes(accounts.alice), 70);
+            assert_eq!(psp22.get_votes(accounts.charlie), 30);
+
+            assert_eq!(psp22.approve(accounts.django, 10), Ok(()));
+            set_caller(accounts.django);
+            assert_eq!(psp22.transfer_from(accounts.alice, accounts.django, 10, Vec::new()), Ok(()));
+            assert_eq!(psp22.get_votes(accounts.alice), 60);
+            assert_eq!(psp22.get_votes(accounts.django), 0);
+
+            set_caller(accounts.alice);
+            assert_eq!(psp22.mint(accounts.bob, 5), Ok(()));
+            assert_eq!(psp22.get_votes(accounts.charlie), 35);
+            assert_eq!(psp22.burn(20), Ok(()));
+            assert_eq!(psp22.get_votes(accounts.alice), 40);
+        }
+
+        #[ink::test]
+        fn get_past_votes_returns_historical_votes() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.delegate(accounts.alice), Ok(()));
+            advance_block();
+            assert_eq!(psp22.transfer(accounts.bob, 40, Vec::new()), Ok(()));
+            advance_block();
+            assert_eq!(psp22.delegate(accounts.charlie), Ok(()));
+
+            assert_eq!(psp22.get_past_votes(accounts.alice, 0), 100);
+            assert_eq!(psp22.get_past_votes(accounts.alice, 1), 60);
+            assert_eq!(psp22.get_past_votes(accounts.alice, 2), 0);
+            assert_eq!(psp22.get_past_votes(accounts.charlie, 1), 0);
+            assert_eq!(psp22.get_past_votes(accounts.charlie, 2), 60);
+        }
+
+        #[ink::test]
+        fn max_flash_loan_is_limited_by_cap() {
+            let psp22 = Psp22Ink::new_capped(100, 1000, None, None, 0).unwrap();
+            assert_eq!(psp22.max_flash_loan(), 900);
+
+            let psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.max_flash_loan(), Balance::MAX - 100);
+        }
+
+        #[ink::test]
+        fn flash_fee_works() {
+            let psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.flash_fee(10_000), 9);
+            assert_eq!(psp22.flash_fee(1_000), 0);
+            assert_eq!(psp22.flash_fee(Balance::MAX), Balance::MAX / 10_000 * FLASH_FEE_BPS + 1);
+        }
+
+        #[ink::test]
+        fn flash_loan_above_max_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new_capped(100, 1000, None, None, 0).unwrap();
+
+            assert_eq!(psp22.flash_loan(accounts.bob, 901, Vec::new()), Err(PSP22Error::CapExceeded));
+            assert_eq!(psp22.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn flash_loan_fails_when_paused() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.pause(), Ok(()));
+
+            assert_eq!(psp22.flash_loan(accounts.bob, 10, Vec::new()), Err(PSP22Error::Paused));
+        }
+
+        #[ink::test]
+        fn create_vesting_schedule_locks_tokens() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let contract = ink::env::test::callee::<ink::env::DefaultEnvironment>();
+
+            assert_eq!(psp22.create_vesting_schedule(accounts.bob, 40, 1000, 100, 400), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.alice), 60);
+            assert_eq!(psp22.balance_of(contract), 40);
+            assert_eq!(psp22.balance_of(accounts.bob), 0);
+            assert_transfer_event(last_event(), Some(accounts.alice), Some(contract), 40);
+            assert_eq!(
+                psp22.vesting_schedule(accounts.bob),
+                Some(VestingSchedule { total: 40, released: 0, start: 1000, cliff: 100, duration: 400 })
+            );
+        }
+
+        #[ink::test]
+        fn create_vesting_schedule_validates_input() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.create_vesting_schedule(accounts.bob, 0, 1000, 0, 400), Err(PSP22Error::InvalidVestingSchedule));
+            assert_eq!(psp22.vesting_schedule(accounts.bob), None);
+            assert_eq!(psp22.create_vesting_schedule(accounts.bob, 40, 1000, 0, 0), Err(PSP22Error::InvalidVestingSchedule));
+            assert_eq!(psp22.create_vesting_schedule(accounts.bob, 40, 1000, 500, 400), Err(PSP22Error::InvalidVestingSchedule));
+            assert_eq!(psp22.create_vesting_schedule(accounts.bob, 101, 1000, 0, 400), Err(PSP22Error::InsufficientBalance));
+            assert_eq!(psp22.create_vesting_schedule(accounts.bob, 40, 1000, 0, 400), Ok(()));
+            assert_eq!(psp22.create_vesting_schedule(accounts.bob, 10, 1000, 0, 400), Err(PSP22Error::VestingScheduleExists));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.create_vesting_schedule(accounts.charlie, 0, 1000, 0, 400), Err(PSP22Error::Custom("CallerIsNotOwner".to_string())));
+        }
+
+        #[ink::test]
+        fn vesting_releases_linearly_after_cliff() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.create_vesting_schedule(accounts.bob, 40, 1000, 100, 400), Ok(()));
+
+            set_block_timestamp(1099);
+            assert_eq!(psp22.vested_amount(accounts.bob), 0);
+            assert_eq!(psp22.release(accounts.bob), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.bob), 0);
+
+            set_block_timestamp(1100);
+            assert_eq!(psp22.vested_amount(accounts.bob), 10);
+            assert_eq!(psp22.releasable(accounts.bob), 10);
+            set_caller(accounts.charlie);
+            assert_eq!(psp22.release(accounts.bob), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.bob), 10);
+            assert_eq!(psp22.releasable(accounts.bob), 0);
+
+            set_block_timestamp(1300);
+            assert_eq!(psp22.vested_amount(accounts.bob), 30);
+            assert_eq!(psp22.releasable(accounts.bob), 20);
+
+            set_block_timestamp(5000);
+            assert_eq!(psp22.release(accounts.bob), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.bob), 40);
+            assert_eq!(psp22.releasable(accounts.bob), 0);
+            assert_eq!(psp22.vesting_schedule(accounts.bob).unwrap().released, 40);
+        }
+
+        #[ink::test]
+        fn fully_released_vesting_schedule_can_be_replaced() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.create_vesting_schedule(accounts.bob, 40, 1000, 0, 400), Ok(()));
+
+            set_block_timestamp(1200);
+            assert_eq!(psp22.release(accounts.bob), Ok(()));
+            assert_eq!(psp22.create_vesting_schedule(accounts.bob, 10, 2000, 0, 400), Err(PSP22Error::VestingScheduleExists));
+
+            set_block_timestamp(1400);
+            assert_eq!(psp22.release(accounts.bob), Ok(()));
+            assert_eq!(psp22.create_vesting_schedule(accounts.bob, 10, 2000, 0, 400), Ok(()));
+            assert_eq!(
+                psp22.vesting_schedule(accounts.bob),
+                Some(VestingSchedule { total: 10, released: 0, start: 2000, cliff: 0, duration: 400 })
+            );
+            assert_eq!(psp22.balance_of(accounts.alice), 50);
+            assert_eq!(psp22.balance_of(accounts.bob), 40);
+        }
+
+        #[ink::test]
+        fn transfer_locked_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            set_block_timestamp(1000);
+
+            assert_eq!(psp22.transfer_locked(accounts.bob, 30, 2000), Ok(()));
+            assert_transfer_event(last_event(), Some(accounts.alice), Some(accounts.bob), 30);
+            assert_eq!(psp22.balance_of(accounts.bob), 30);
+            assert_eq!(psp22.locked_balance_of(accounts.bob), 30);
+            assert_eq!(psp22.unlocked_balance_of(accounts.bob), 0);
+            assert_eq!(psp22.unlocked_balance_of(accounts.alice), 70);
+
+            set_block_timestamp(2000);
+            assert_eq!(psp22.locked_balance_of(accounts.bob), 0);
+            assert_eq!(psp22.unlocked_balance_of(accounts.bob), 30);
+        }
+
+        #[ink::test]
+        fn locked_tokens_cannot_be_moved() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            set_block_timestamp(1000);
+            assert_eq!(psp22.transfer_locked(accounts.bob, 30, 2000), Ok(()));
+            assert_eq!(psp22.transfer(accounts.bob, 10, Vec::new()), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.transfer(accounts.charlie, 11, Vec::new()), Err(PSP22Error::BalanceLocked));
+            assert_eq!(psp22.transfer(accounts.charlie, 41, Vec::new()), Err(PSP22Error::InsufficientBalance));
+            assert_eq!(psp22.burn(11), Err(PSP22Error::BalanceLocked));
+            assert_eq!(psp22.approve(accounts.charlie, 40), Ok(()));
+            assert_eq!(psp22.transfer(accounts.charlie, 10, Vec::new()), Ok(()));
+
+            set_caller(accounts.charlie);
+            assert_eq!(psp22.transfer_from(accounts.bob, accounts.charlie, 1, Vec::new()), Err(PSP22Error::BalanceLocked));
+
+            set_block_timestamp(2000);
+            assert_eq!(psp22.transfer_from(accounts.bob, accounts.charlie, 30, Vec::new()), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn transfer_locked_drops_expired_locks() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            set_block_timestamp(1000);
+            assert_eq!(psp22.transfer_locked(accounts.bob, 10, 1500), Ok(()));
+            assert_eq!(psp22.transfer_locked(accounts.bob, 20, 3000), Ok(()));
+
+            set_block_timestamp(2000);
+            assert_eq!(psp22.transfer_locked(accounts.bob, 5, 2500), Ok(()));
+            assert_eq!(psp22.locked_balance_of(accounts.bob), 25);
+            assert_eq!(psp22.unlocked_balance_of(accounts.bob), 10);
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.transfer_locked(accounts.charlie, 11, 2500), Err(PSP22Error::BalanceLocked));
+        }
+
+        #[ink::test]
+        fn batch_transfer_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let events_before = recorded_events().len();
+
+            assert_eq!(psp22.batch_transfer(vec![(accounts.bob, 10), (accounts.charlie, 20), (accounts.bob, 5)]), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.alice), 65);
+            assert_eq!(psp22.balance_of(accounts.bob), 15);
+            assert_eq!(psp22.balance_of(accounts.charlie), 20);
+            let events: Vec<Event> = recorded_events().into_iter().skip(events_before).collect();
+            assert_eq!(events.len(), 3);
+            let mut events = events.into_iter();
+            assert_transfer_event(events.next().unwrap(), Some(accounts.alice), Some(accounts.bob), 10);
+            assert_transfer_event(events.next().unwrap(), Some(accounts.alice), Some(accounts.charlie), 20);
+            assert_transfer_event(events.next().unwrap(), Some(accounts.alice), Some(accounts.bob), 5);
+        }
+
+        #[ink::test]
+        fn batch_transfer_fails_atomically() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(
+                psp22.batch_transfer(vec![(accounts.bob, 10), (AccountId::from([0x0; 32]), 20)]),
+                Err(PSP22Error::BatchTransferFailed(1, Box::new(PSP22Error::ZeroRecipientAddress)))
+            );
+            assert_eq!(psp22.add_to_blacklist(accounts.charlie), Ok(()));
+            assert_eq!(
+                psp22.batch_transfer(vec![(accounts.bob, 10), (accounts.charlie, 20)]),
+                Err(PSP22Error::BatchTransferFailed(1, Box::new(PSP22Error::Blacklisted(accounts.charlie))))
+            );
+            assert_eq!(
+                psp22.batch_transfer(vec![(accounts.bob, Balance::MAX), (accounts.django, 1)]),
+                Err(PSP22Error::BatchTransferFailed(1, Box::new(PSP22Error::Overflow)))
+            );
+            assert_eq!(psp22.batch_transfer(vec![(accounts.bob, 60), (accounts.django, 41)]), Err(PSP22Error::InsufficientBalance));
+            assert_eq!(psp22.balance_of(accounts.alice), 100);
+            assert_eq!(psp22.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn batch_transfer_from_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.approve(accounts.bob, 30), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                psp22.batch_transfer_from(accounts.alice, vec![(accounts.charlie, 20), (accounts.django, 11)]),
+                Err(PSP22Error::InsufficientAllowance)
+            );
+            assert_eq!(psp22.batch_transfer_from(accounts.alice, vec![(accounts.charlie, 20), (accounts.django, 10)]), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.alice), 70);
+            assert_eq!(psp22.balance_of(accounts.charlie), 20);
+            assert_eq!(psp22.balance_of(accounts.django), 10);
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn claim_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let contract = ink::env::test::callee::<ink::env::DefaultEnvironment>();
+            let leaves = [
+                psp22.merkle_leaf(0, accounts.bob, 10),
+                psp22.merkle_leaf(1, accounts.charlie, 20),
+                psp22.merkle_leaf(2, accounts.django, 30),
+            ];
+            let node = psp22.merkle_parent(leaves[0], leaves[1]);
+            let root = psp22.merkle_parent(node, leaves[2]);
+
+            assert_eq!(psp22.set_merkle_root(root, 60), Ok(()));
+            assert_eq!(psp22.merkle_root(), Some(root));
+            assert_eq!(psp22.airdrop_pool(), 60);
+            assert_eq!(psp22.balance_of(contract), 60);
+
+            set_caller(accounts.eve);
+            assert_eq!(psp22.claim(1, accounts.charlie, 20, vec![leaves[0], leaves[2]]), Ok(()));
+            assert!(matches!(last_event(), Event::Claimed(Claimed { account, index: 1, amount: 20 }) if account == accounts.charlie));
+            assert_eq!(psp22.balance_of(accounts.charlie), 20);
+            assert_eq!(psp22.airdrop_pool(), 40);
+            assert!(psp22.is_claimed(1));
+            assert!(!psp22.is_claimed(0));
+
+            assert_eq!(psp22.claim(2, accounts.django, 30, vec![node]), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.django), 30);
+            assert_eq!(psp22.airdrop_pool(), 10);
+        }
+
+        #[ink::test]
+        fn claim_with_invalid_proof_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let leaves = [psp22.merkle_leaf(0, accounts.bob, 10), psp22.merkle_leaf(1, accounts.charlie, 20)];
+            let root = psp22.merkle_parent(leaves[0], leaves[1]);
+
+            assert_eq!(psp22.claim(0, accounts.bob, 10, vec![leaves[1]]), Err(PSP22Error::InvalidMerkleProof));
+            assert_eq!(psp22.set_merkle_root(root, 30), Ok(()));
+            assert_eq!(psp22.claim(0, accounts.bob, 11, vec![leaves[1]]), Err(PSP22Error::InvalidMerkleProof));
+            assert_eq!(psp22.claim(0, accounts.charlie, 10, vec![leaves[1]]), Err(PSP22Error::InvalidMerkleProof));
+            assert_eq!(psp22.claim(1, accounts.bob, 10, vec![leaves[1]]), Err(PSP22Error::InvalidMerkleProof));
+            assert_eq!(psp22.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn claim_twice_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let leaves = [psp22.merkle_leaf(200, accounts.bob, 10), psp22.merkle_leaf(201, accounts.charlie, 20)];
+            let root = psp22.merkle_parent(leaves[0], leaves[1]);
+            assert_eq!(psp22.set_merkle_root(root, 30), Ok(()));
+
+            assert_eq!(psp22.claim(200, accounts.bob, 10, vec![leaves[1]]), Ok(()));
+            assert_eq!(psp22.claim(200, accounts.bob, 10, vec![leaves[1]]), Err(PSP22Error::AirdropAlreadyClaimed));
+            assert!(psp22.is_claimed(200));
+            assert!(!psp22.is_claimed(201));
+            assert_eq!(psp22.balance_of(accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn claim_beyond_pool_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let leaves = [psp22.merkle_leaf(0, accounts.bob, 10), psp22.merkle_leaf(1, accounts.charlie, 20)];
+            let root = psp22.merkle_parent(leaves[0], leaves[1]);
+            assert_eq!(psp22.set_merkle_root(root, 15), Ok(()));
+
+            assert_eq!(psp22.claim(1, accounts.charlie, 20, vec![leaves[0]]), Err(PSP22Error::InsufficientBalance));
+            assert!(!psp22.is_claimed(1));
+        }
+
+        #[ink::test]
+        fn set_merkle_root_requires_owner() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.set_merkle_root([1; 32], 0), Err(PSP22Error::Custom("CallerIsNotOwner".to_string())));
+            assert_eq!(psp22.merkle_root(), None);
+        }
+
+        #[ink::test]
+        fn transfer_ownership_moves_owner_roles() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.renounce_role(PAUSER, accounts.alice), Ok(()));
+
+            assert_eq!(psp22.transfer_ownership(accounts.bob), Ok(()));
+            for role in [DEFAULT_ADMIN_ROLE, MINTER, COMPLIANCE] {
+                assert!(!psp22.has_role(role, accounts.alice));
+                assert!(psp22.has_role(role, accounts.bob));
+            }
+            //A role the previous owner gave up is not handed over.
+            assert!(!psp22.has_role(PAUSER, accounts.bob));
+            assert_eq!(psp22.mint(accounts.alice, 1), Err(PSP22Error::Custom("MissingRole".to_string())));
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.mint(accounts.alice, 1), Ok(()));
+            assert_eq!(psp22.grant_role(PAUSER, accounts.bob), Ok(()));
+        }
+
+        #[ink::test]
+        fn renounce_ownership_revokes_owner_roles() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            assert_eq!(psp22.renounce_ownership(), Ok(()));
+            for role in [DEFAULT_ADMIN_ROLE, MINTER, PAUSER, COMPLIANCE] {
+                assert!(!psp22.has_role(role, accounts.alice));
+            }
+            assert_eq!(psp22.pause(), Err(PSP22Error::Custom("MissingRole".to_string())));
+        }
+
+        #[ink::test]
+        fn set_role_admin_works() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.grant_role(PAUSER, accounts.bob), Ok(()));
+
+            assert_eq!(psp22.set_role_admin(MINTER, PAUSER), Ok(()));
+            assert_eq!(psp22.get_role_admin(MINTER), PAUSER);
+            if let Event::RoleAdminChanged(RoleAdminChanged { role, previous_admin_role, new_admin_role }) = last_event() {
+                assert_eq!(role, MINTER);
+                assert_eq!(previous_admin_role, DEFAULT_ADMIN_ROLE);
+                assert_eq!(new_admin_role, PAUSER);
+            } else {
+                panic!("encountered unexpected event kind: expected a RoleAdminChanged event")
+            }
+
+            //Alice keeps the pauser role but bob can now manage minters too.
+            set_caller(accounts.bob);
+            assert_eq!(psp22.grant_role(MINTER, accounts.charlie), Ok(()));
+            assert_eq!(psp22.revoke_role(MINTER, accounts.alice), Ok(()));
+            assert_eq!(psp22.set_role_admin(MINTER, DEFAULT_ADMIN_ROLE), Ok(()));
+            assert_eq!(psp22.grant_role(MINTER, accounts.django), Err(AccessControlError::MissingRole));
+        }
+
+        #[ink::test]
+        fn set_role_admin_requires_current_admin_role() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+
+            set_caller(accounts.bob);
+            assert_eq!(psp22.set_role_admin(MINTER, PAUSER), Err(AccessControlError::MissingRole));
+            assert_eq!(psp22.get_role_admin(MINTER), DEFAULT_ADMIN_ROLE);
+        }
+
+        #[ink::test]
+        fn blacklisted_spender_cannot_use_allowance() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.approve(accounts.bob, 50), Ok(()));
+            assert_eq!(psp22.add_to_blacklist(accounts.bob), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                psp22.transfer_from(accounts.alice, accounts.charlie, 10, Vec::new()),
+                Err(PSP22Error::Blacklisted(accounts.bob))
+            );
+            assert_eq!(psp22.burn_from(accounts.alice, 10), Err(PSP22Error::Blacklisted(accounts.bob)));
+            assert_eq!(
+                psp22.batch_transfer_from(accounts.alice, vec![(accounts.charlie, 10)]),
+                Err(PSP22Error::Blacklisted(accounts.bob))
+            );
+            assert_eq!(psp22.balance_of(accounts.alice), 100);
+            assert_eq!(psp22.allowance(accounts.alice, accounts.bob), 50);
+        }
+
+        #[ink::test]
+        fn only_token_operations_are_allowed_during_flash_loan() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            psp22.flash_loan_lock.set(&true);
+
+            assert_eq!(psp22.approve(accounts.bob, 10), Ok(()));
+            assert_eq!(psp22.transfer(accounts.bob, 1, Vec::new()), Ok(()));
+            assert_eq!(psp22.batch_transfer(vec![(accounts.bob, 1)]), Ok(()));
+            assert_eq!(psp22.burn(1), Ok(()));
+            assert_eq!(psp22.mint(accounts.alice, 1), Ok(()));
+            assert_eq!(psp22.delegate(accounts.alice), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.alice), 98);
+
+            assert_eq!(psp22.flash_loan(accounts.bob, 1, Vec::new()), Err(PSP22Error::FlashLoanInProgress));
+            assert_eq!(psp22.pause(), Err(PSP22Error::FlashLoanInProgress));
+            assert_eq!(psp22.add_to_blacklist(accounts.bob), Err(PSP22Error::FlashLoanInProgress));
+            assert_eq!(psp22.transfer_ownership(accounts.bob), Err(OwnableError::FlashLoanInProgress));
+            assert_eq!(psp22.grant_role(MINTER, accounts.bob), Err(AccessControlError::FlashLoanInProgress));
+
+            psp22.flash_loan_lock.set(&false);
+            assert_eq!(psp22.pause(), Ok(()));
+        }
+
+        #[ink::test]
+        fn transfer_locked_zero_value_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            set_block_timestamp(1000);
+
+            assert_eq!(psp22.transfer_locked(accounts.bob, 0, 2000), Err(PSP22Error::ZeroLockValue));
+            assert_eq!(psp22.locked_balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn transfer_locked_merges_locks_with_same_unlock_time() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            set_block_timestamp(1000);
+
+            assert_eq!(psp22.transfer_locked(accounts.bob, 10, 2000), Ok(()));
+            assert_eq!(psp22.transfer_locked(accounts.bob, 15, 2000), Ok(()));
+            assert_eq!(psp22.locks.get(accounts.bob), Some(vec![(2000, 25)]));
+            assert_eq!(psp22.locked_balance_of(accounts.bob), 25);
+        }
+
+        #[ink::test]
+        fn transfer_locked_above_max_locks_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            set_block_timestamp(1000);
+            for i in 0..MAX_LOCKS_PER_ACCOUNT as u64 {
+                assert_eq!(psp22.transfer_locked(accounts.bob, 1, 2000 + i), Ok(()));
+            }
+
+            assert_eq!(psp22.transfer_locked(accounts.bob, 1, 5000), Err(PSP22Error::TooManyLocks));
+            assert_eq!(psp22.balance_of(accounts.bob), MAX_LOCKS_PER_ACCOUNT as Balance);
+            //Locks ending at an existing unlock time are still merged.
+            assert_eq!(psp22.transfer_locked(accounts.bob, 1, 2000), Ok(()));
+            //Expired locks free up room.
+            set_block_timestamp(2000);
+            assert_eq!(psp22.transfer_locked(accounts.bob, 1, 5000), Ok(()));
+        }
+
+        #[ink::test]
+        fn transfer_locked_beyond_max_duration_fails() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            set_block_timestamp(1000);
+
+            assert_eq!(psp22.transfer_locked(accounts.bob, 1, Timestamp::MAX), Err(PSP22Error::LockTooLong));
+            assert_eq!(psp22.transfer_locked(accounts.bob, 1, 1001 + MAX_LOCK_DURATION), Err(PSP22Error::LockTooLong));
+            assert_eq!(psp22.transfer_locked(accounts.bob, 1, 1000 + MAX_LOCK_DURATION), Ok(()));
+        }
+
+        #[ink::test]
+        fn lock_slots_filled_by_others_free_up() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.transfer(accounts.charlie, 50, Vec::new()), Ok(()));
+            set_block_timestamp(1000);
+            //Charlie fills all of Bob's lock slots with one-unit locks as far ahead as allowed.
+            set_caller(accounts.charlie);
+            for i in 0..MAX_LOCKS_PER_ACCOUNT as u64 {
+                assert_eq!(psp22.transfer_locked(accounts.bob, 1, 1000 + MAX_LOCK_DURATION - i), Ok(()));
+            }
+            set_caller(accounts.alice);
+            assert_eq!(psp22.transfer_locked(accounts.bob, 10, 5000), Err(PSP22Error::TooManyLocks));
+
+            set_block_timestamp(1000 + MAX_LOCK_DURATION);
+            assert_eq!(psp22.transfer_locked(accounts.bob, 10, 2000 + MAX_LOCK_DURATION), Ok(()));
+            assert_eq!(psp22.locked_balance_of(accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn set_merkle_root_starts_new_round() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            let leaves = [psp22.merkle_leaf(0, accounts.bob, 10), psp22.merkle_leaf(1, accounts.charlie, 20)];
+            let root = psp22.merkle_parent(leaves[0], leaves[1]);
+            assert_eq!(psp22.set_merkle_root(root, 30), Ok(()));
+            assert_eq!(psp22.claim(0, accounts.bob, 10, vec![leaves[1]]), Ok(()));
+
+            //The 20 tokens left in the pool go back to the owner, and index 0 can be claimed again.
+            let leaves = [psp22.merkle_leaf(0, accounts.bob, 5), psp22.merkle_leaf(1, accounts.django, 5)];
+            let root = psp22.merkle_parent(leaves[0], leaves[1]);
+            assert_eq!(psp22.set_merkle_root(root, 10), Ok(()));
+            assert_eq!(psp22.airdrop_pool(), 10);
+            assert_eq!(psp22.balance_of(accounts.alice), 80);
+            assert!(!psp22.is_claimed(0));
+            assert_eq!(psp22.claim(0, accounts.bob, 5, vec![leaves[1]]), Ok(()));
+            assert_eq!(psp22.balance_of(accounts.bob), 15);
+
+            //A round with an empty pool ends the airdrop and returns what is left.
+            assert_eq!(psp22.set_merkle_root([0; 32], 0), Ok(()));
+            assert_eq!(psp22.airdrop_pool(), 0);
+            assert_eq!(psp22.balance_of(accounts.alice), 85);
+        }
+
+        #[ink::test]
+        fn set_merkle_root_fails_for_blacklisted_owner() {
+            let accounts = default_accounts();
+            let mut psp22 = Psp22Ink::new(100);
+            assert_eq!(psp22.add_to_blacklist(accounts.alice), Ok(()));
+
+            assert_eq!(psp22.set_merkle_root([1; 32], 10), Err(PSP22Error::Blacklisted(accounts.alice)));
+            assert_eq!(psp22.airdrop_pool(), 0);
+        }
+    }
+
+    #[cfg(test)]
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+        use std::collections::HashMap;
+
+        const ACCOUNTS: usize = 6;
+
+        #[derive(Debug, Clone)]
+        enum Operation {
+            Transfer { caller: usize, to: usize, value: Balance },
+            TransferFrom { caller: usize, from: usize, to: usize, value: Balance },
+            Approve { caller: usize, spender: usize, value: Balance },
+            IncreaseAllowance { caller: usize, spender: usize, value: Balance },
+            DecreaseAllowance { caller: usize, spender: usize, value: Balance },
+        }
+
+        fn value() -> impl Strategy<Value = Balance> {
+            prop_oneof![
+                8 => 0..=150u128,
+                1 => Just(Balance::MAX),
+                1 => any::<Balance>(),
+            ]
+        }
+
+        fn operation() -> impl Strategy<Value = Operation> {
+            let account = 0..ACCOUNTS;
+            prop_oneof![
+                (account.clone(), account.clone(), value())
+                    .prop_map(|(caller, to, value)| Operation::Transfer { caller, to, value }),
+                (account.clone(), account.clone(), account.clone(), value())
+                    .prop_map(|(caller, from, to, value)| Operation::TransferFrom { caller, from, to, value }),
+                (account.clone(), account.clone(), value())
+                    .prop_map(|(caller, spender, value)| Operation::Approve { caller, spender, value }),
+                (account.clone(), account.clone(), value())
+                    .prop_map(|(caller, spender, value)| Operation::IncreaseAllowance { caller, spender, value }),
+                (account.clone(), account, value())
+                    .prop_map(|(caller, spender, value)| Operation::DecreaseAllowance { caller, spender, value }),
+            ]
+        }
+
+        //Reference model of the token bookkeeping, indexed by account position.
+        #[derive(Default)]
+        struct Model {
+            balances: HashMap<usize, Balance>,
+            allowances: HashMap<(usize, usize), Balance>,
+        }
+
+        impl Model {
+            fn balance(&self, account: usize) -> Balance {
+                self.balances.get(&account).copied().unwrap_or_default()
+            }
+
+            fn allowance(&self, owner: usize, spender: usize) -> Balance {
+                self.allowances.get(&(owner, spender)).copied().unwrap_or_default()
+            }
+
+            fn move_balance(&mut self, from: usize, to: usize, value: Balance) -> Result<()> {
+                let from_balance = self.balance(from).checked_sub(value).ok_or(PSP22Error::InsufficientBalance)?;
+                self.balances.insert(from, from_balance);
+                let to_balance = self.balance(to).checked_add(value).ok_or(PSP22Error::Overflow)?;
+                self.balances.insert(to, to_balance);
+                Ok(())
+            }
+
+            fn apply(&mut self, operation: &Operation) -> Result<()> {
+                match *operation {
+                    Operation::Transfer { caller, to, value } => self.move_balance(caller, to, value),
+                    Operation::TransferFrom { caller, from, to, value } => {
+                        let allowance = self.allowance(from, caller).checked_sub(value).ok_or(PSP22Error::InsufficientAllowance)?;
+                        if self.balance(from) < value {
+                            return Err(PSP22Error::InsufficientBalance);
+                        }
+                        self.allowances.insert((from, caller), allowance);
+                        self.move_balance(from, to, value)
+                    }
+                    Operation::Approve { caller, spender, value } => {
+                        self.allowances.insert((caller, spender), value);
+                        Ok(())
+                    }
+                    Operation::IncreaseAllowance { caller, spender, value } => {
+                        let allowance = self.allowance(caller, spender).checked_add(value).ok_or(PSP22Error::Overflow)?;
+                        self.allowances.insert((caller, spender), allowance);
+                        Ok(())
+                    }
+                    Operation::DecreaseAllowance { caller, spender, value } => {
+                        let allowance = self.allowance(caller, spender).checked_sub(value).ok_or(PSP22Error::InsufficientAllowance)?;
+                        self.allowances.insert((caller, spender), allowance);
+                        Ok(())
+                    }
+                }
+            }
+        }
+
+        fn execute(psp22: &mut Psp22Ink, accounts: &[AccountId; ACCOUNTS], operation: &Operation) -> Result<()> {
+            let set_caller = |caller: usize| ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts[caller]);
+            match *operation {
+                Operation::Transfer { caller, to, value } => {
+                    set_caller(caller);
+                    psp22.transfer(accounts[to], value, Vec::new())
+                }
+                Operation::TransferFrom { caller, from, to, value } => {
+                    set_caller(caller);
+                    psp22.transfer_from(accounts[from], accounts[to], value, Vec::new())
+                }
+                Operation::Approve { caller, spender, value } => {
+                    set_caller(caller);
+                    psp22.approve(accounts[spender], value)
+                }
+                Operation::IncreaseAllowance { caller, spender, value } => {
+                    set_caller(caller);
+                    psp22.increase_allowance(accounts[spender], value)
+                }
+                Operation::DecreaseAllowance { caller, spender, value } => {
+                    set_caller(caller);
+                    psp22.decrease_allowance(accounts[spender], value)
+                }
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn supply_and_allowance_invariants_hold(
+                initial_supply in prop_oneof![0..=1_000u128, Just(Balance::MAX)],
+                operations in prop::collection::vec(operation(), 1..64),
+            ) {
+                ink::env::test::run_test::<ink::env::DefaultEnvironment, _>(|default_accounts| {
+                    let accounts = [
+                        default_accounts.alice,
+                        default_accounts.bob,
+                        default_accounts.charlie,
+                        default_accounts.django,
+                        default_accounts.eve,
+                        default_accounts.frank,
+                    ];
+                    ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts[0]);
+                    let mut psp22 = Psp22Ink::new(initial_supply);
+                    let mut model = Model::default();
+                    model.balances.insert(0, initial_supply);
+
+                    for (step, operation) in operations.iter().enumerate() {
+                        let expected = model.apply(operation);
+                        let actual = execute(&mut psp22, &accounts, operation);
+                        assert_eq!(actual, expected, "step {}: unexpected result of {:?}", step, operation);
+
+                        //The sum of all balances always equals the total supply.
+                        let mut sum: Balance = 0;
+                        for (index, account) in accounts.iter().enumerate() {
+                            let balance = psp22.balance_of(*account);
+                            assert_eq!(balance, model.balance(index), "step {}: balance of account {} diverged", step, index);
+                            sum = sum.checked_add(balance).expect("sum of balances overflows the total supply");
+                        }
+                        assert_eq!(sum, psp22.total_supply(), "step {}: sum of balances differs from total supply", step);
+                        assert_eq!(psp22.total_supply(), initial_supply, "step {}: total supply changed", step);
+
+                        //Allowances only change through the expected operations and never underflow.
+                        for (owner_index, owner) in accounts.iter().enumerate() {
+                            for (spender_index, spender) in accounts.iter().enumerate() {
+                                assert_eq!(
+                                    psp22.allowance(*owner, *spender),
+                                    model.allowance(owner_index, spender_index),
+                                    "step {}: allowance of {} for {} diverged",
+                                    step,
+                                    owner_index,
+                                    spender_index,
+                                );
+                            }
+                        }
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod abi_conformance {
+        use serde_json::Value;
+
+        extern "Rust" {
+            fn __ink_generate_metadata() -> ink::metadata::InkProject;
+        }
+
+        fn contract_metadata() -> Value {
+            let metadata = unsafe { __ink_generate_metadata() };
+            serde_json::to_value(metadata).expect("failed to serialize the contract metadata")
+        }
+
+        fn psp22_standard() -> Value {
+            serde_json::from_str(include_str!("spec/psp22.json")).expect("failed to parse the PSP22 standard")
+        }
+
+        fn registry_type<'a>(types: &'a [Value], id: &Value) -> &'a Value {
+            &types
+                .iter()
+                .find(|ty| &ty["id"] == id)
+                .unwrap_or_else(|| panic!("type {} is missing from the type registry", id))["type"]
+        }
+
+        //Checks that the enum `ty` starts with the variants of the standard type `expected`, in the same order
+        //and with the same names and fields. Implementations may append their own variants after them.
+        fn check_variants(types: &[Value], standard_types: &Value, ty: &Value, expected: &Value) -> Result<(), String> {
+            let mut variants: Vec<&Value> = ty["def"]["variant"]["variants"]
+                .as_array()
+                .ok_or("it is not an enum")?
+                .iter()
+                .collect();
+            variants.sort_by_key(|variant| variant["index"].as_u64());
+            let expected_variants = expected["variants"].as_array().expect("standard type has no variants");
+            if variants.len() < expected_variants.len() {
+                return Err(format!("it has {} variants instead of at least {}", variants.len(), expected_variants.len()));
+            }
+            for (index, (variant, expected_variant)) in variants.into_iter().zip(expected_variants).enumerate() {
+                let name = expected_variant["name"].as_str().expect("standard variant has no name");
+                if variant["index"].as_u64() != Some(index as u64) || variant["name"].as_str() != Some(name) {
+                    return Err(format!("variant {} is not at index {}", name, index));
+                }
+                let fields: Vec<String> = variant["fields"]
+                    .as_array()
+                    .map(|fields| fields.iter().map(|field| type_name(types, standard_types, &field["type"])).collect())
+                    .unwrap_or_default();
+                let expected_fields: Vec<&str> = expected_variant["fields"]
+                    .as_array()
+                    .expect("standard variant has no fields")
+                    .iter()
+                    .map(|field| field.as_str().expect("standard field has no type"))
+                    .collect();
+                if fields != expected_fields {
+                    return Err(format!("variant {} has fields {:?} instead of {:?}", name, fields, expected_fields));
+                }
+            }
+            Ok(())
+        }
+
+        //Renders the type with the given id of the metadata type registry in Rust syntax, e.g. `Result<(), PSP22Error>`.
+        //Types are named without their crate path, and enums conforming to a type of the standard by the name of
+        //that type, so that any conforming implementation renders the same names.
+        fn type_name(types: &[Value], standard_types: &Value, id: &Value) -> String {
+            let ty = registry_type(types, id);
+            let def = &ty["def"];
+            if let Some(primitive) = def["primitive"].as_str() {
+                return match primitive {
+                    "str" => "String".to_string(),
+                    primitive => primitive.to_string(),
+                };
+            }
+            if let Some(fields) = def["tuple"].as_array() {
+                let fields: Vec<String> = fields.iter().map(|field| type_name(types, standard_types, field)).collect();
+                return format!("({})", fields.join(", "));
+            }
+            if def["sequence"].is_object() {
+                return format!("Vec<{}>", type_name(types, standard_types, &def["sequence"]["type"]));
+            }
+            if def["array"].is_object() {
+                return format!("[{}; {}]", type_name(types, standard_types, &def["array"]["type"]), def["array"]["len"]);
+            }
+            let standard_name = standard_types
+                .as_object()
+                .expect("standard has no types")
+                .iter()
+                .find(|(_, expected)| check_variants(types, standard_types, ty, expected).is_ok());
+            if let Some((standard_name, _)) = standard_name {
+                return standard_name.clone();
+            }
+            let name = ty["path"]
+                .as_array()
+                .and_then(|path| path.last())
+                .and_then(Value::as_str)
+                .unwrap_or_else(|| panic!("type {} has no path", id));
+            let params: Vec<String> = ty["params"]
+                .as_array()
+                .map(|params| params.iter().map(|param| type_name(types, standard_types, &param["type"])).collect())
+                .unwrap_or_default();
+            if params.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}<{}>", name, params.join(", "))
+            }
+        }
+
+        //Every message returns `ink::MessageResult<T>`, i.e. `Result<T, LangError>`; the standard only specifies `T`.
+        fn message_return_type(types: &[Value], standard_types: &Value, id: &Value) -> String {
+            let name = type_name(types, standard_types, id);
+            name.strip_prefix("Result<")
+                .and_then(|name| name.strip_suffix(", LangError>"))
+                .unwrap_or_else(|| panic!("message return type {} is not an ink::MessageResult", name))
+                .to_string()
+        }
+
+        #[test]
+        fn messages_conform_to_psp22_standard() {
+            let metadata = contract_metadata();
+            let standard = psp22_standard();
+            let types = metadata["types"].as_array().expect("metadata has no type registry");
+            let messages = metadata["spec"]["messages"].as_array().expect("metadata has no messages");
+
+            for expected in standard["messages"].as_array().expect("standard has no messages") {
+                let label = expected["label"].as_str().expect("standard message has no label");
+                let message = messages
+                    .iter()
+                    .find(|message| message["label"] == expected["label"])
+                    .unwrap_or_else(|| panic!("message {} is missing", label));
+
+                assert_eq!(message["selector"], expected["selector"], "selector of {} differs from the standard", label);
+                assert_eq!(message["mutates"], expected["mutates"], "mutability of {} differs from the standard", label);
+
+                let args = message["args"].as_array().expect("message has no arguments");
+                let expected_args = expected["args"].as_array().expect("standard message has no arguments");
+                assert_eq!(args.len(), expected_args.len(), "number of arguments of {} differs from the standard", label);
+                for (arg, expected_arg) in args.iter().zip(expected_args) {
+                    assert_eq!(
+                        type_name(types, &standard["types"], &arg["type"]["type"]),
+                        expected_arg["type"].as_str().expect("standard argument has no type"),
+                        "type of argument {} of {} differs from the standard",
+                        expected_arg["label"],
+                        label,
+                    );
+                }
+
+                assert_eq!(
+                    message_return_type(types, &standard["types"], &message["returnType"]["type"]),
+                    expected["returnType"].as_str().expect("standard message has no return type"),
+                    "return type of {} differs from the standard",
+                    label,
+                );
+            }
+        }
+
+        //Reports why the error type does not conform, which `messages_conform_to_psp22_standard` only shows
+        //as a differing return type.
+        #[test]
+        fn error_type_conforms_to_psp22_standard() {
+            let metadata = contract_metadata();
+            let standard = psp22_standard();
+            let types = metadata["types"].as_array().expect("metadata has no type registry");
+            let transfer = metadata["spec"]["messages"]
+                .as_array()
+                .expect("metadata has no messages")
+                .iter()
+                .find(|message| message["label"] == "PSP22::transfer")
+                .expect("message PSP22::transfer is missing");
+
+            //`PSP22::transfer` returns `ink::MessageResult<Result<(), E>>`.
+            let message_result = registry_type(types, &transfer["returnType"]["type"]);
+            let result = registry_type(types, &message_result["params"][0]["type"]);
+            let error = registry_type(types, &result["params"][1]["type"]);
+            if let Err(mismatch) = check_variants(types, &standard["types"], error, &standard["types"]["PSP22Error"]) {
+                panic!("the error type of PSP22::transfer does not conform to PSP22Error: {}", mismatch);
+            }
+        }
+    }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::build_message;
+        use flash_borrower_mock::{FlashBorrowerMockRef, Mode};
+        use psp22_receiver_mock::Psp22ReceiverMockRef;
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        type Event = <Psp22Ink as ::ink::reflect::ContractEventBase>::Type;
+
+        //Decodes the events emitted by `contract` in the extrinsic that produced `result`.
+        fn contract_events<V>(
+            result: &ink_e2e::CallResult<ink_e2e::PolkadotConfig, ink::env::DefaultEnvironment, V>,
+            contract: AccountId,
+        ) -> Vec<Event> {
+            result
+                .events
+                .find::<ink_e2e::events::ContractEmitted<ink::env::DefaultEnvironment>>()
+                .map(|event| event.expect("encountered invalid ContractEmitted event"))
+                .filter(|event| event.contract == contract)
+                .map(|event| {
+                    <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer")
+                })
+                .collect()
+        }
+
+        #[ink_e2e::test]
+        async fn e2e_new_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let constructor = Psp22InkRef::new(1000);
+            let contract_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let total_supply = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.total_supply());
+            let total_supply_result = client.call_dry_run(&ink_e2e::alice(), &total_supply, 0, None).await;
+            assert_eq!(total_supply_result.return_value(), 1000);
+
+            let balance_of = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.balance_of(alice_account));
+            let balance_of_result = client.call_dry_run(&ink_e2e::alice(), &balance_of, 0, None).await;
+            assert_eq!(balance_of_result.return_value(), 1000);
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn e2e_transfer_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let constructor = Psp22InkRef::new(1000);
+            let contract_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let transfer = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.transfer(bob_account, 10, Vec::new()));
+            let transfer_result = client
+                .call(&ink_e2e::alice(), transfer, 0, None)
+                .await
+                .expect("transfer failed");
+            let events = contract_events(&transfer_result, contract_account_id.clone());
+            assert_eq!(events.len(), 1);
+            assert!(matches!(
+                &events[0],
+                Event::Transfer(Transfer { from, to, value: 10 }) if *from == Some(alice_account) && *to == Some(bob_account)
+            ));
+            assert_eq!(transfer_result.return_value(), Ok(()));
+
+            let balance_of = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.balance_of(alice_account));
+            let balance_of_result = client.call_dry_run(&ink_e2e::alice(), &balance_of, 0, None).await;
+            assert_eq!(balance_of_result.return_value(), 990);
+
+            let balance_of = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.balance_of(bob_account));
+            let balance_of_result = client.call_dry_run(&ink_e2e::alice(), &balance_of, 0, None).await;
+            assert_eq!(balance_of_result.return_value(), 10);
+
+            //Bob cannot spend more than he received.
+            let transfer = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.transfer(alice_account, 11, Vec::new()));
+            let transfer_result = client.call_dry_run(&ink_e2e::bob(), &transfer, 0, None).await;
+            assert_eq!(transfer_result.return_value(), Err(PSP22Error::InsufficientBalance));
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn e2e_approve_and_transfer_from_work(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let constructor = Psp22InkRef::new(1000);
+            let contract_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+            let charlie_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Charlie);
+
+            let approve = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.approve(bob_account, 100));
+            let approve_result = client
+                .call(&ink_e2e::alice(), approve, 0, None)
+                .await
+                .expect("approve failed");
+            let events = contract_events(&approve_result, contract_account_id.clone());
+            assert_eq!(events.len(), 1);
+            assert!(matches!(
+                &events[0],
+                Event::Approval(Approval { owner, spender, value: 100 }) if *owner == alice_account && *spender == bob_account
+            ));
+
+            //Bob cannot spend more than the approved amount.
+            let transfer_from = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.transfer_from(alice_account, charlie_account, 101, Vec::new()));
+            let transfer_from_result = client.call_dry_run(&ink_e2e::bob(), &transfer_from, 0, None).await;
+            assert_eq!(transfer_from_result.return_value(), Err(PSP22Error::InsufficientAllowance));
+
+            let transfer_from = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.transfer_from(alice_account, charlie_account, 60, Vec::new()));
+            let transfer_from_result = client
+                .call(&ink_e2e::bob(), transfer_from, 0, None)
+                .await
+                .expect("transfer_from failed");
+            let events = contract_events(&transfer_from_result, contract_account_id.clone());
+            assert_eq!(events.len(), 1);
+            assert!(matches!(
+                &events[0],
+                Event::Transfer(Transfer { from, to, value: 60 }) if *from == Some(alice_account) && *to == Some(charlie_account)
+            ));
+            assert_eq!(transfer_from_result.return_value(), Ok(()));
+
+            let allowance = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.allowance(alice_account, bob_account));
+            let allowance_result = client.call_dry_run(&ink_e2e::alice(), &allowance, 0, None).await;
+            assert_eq!(allowance_result.return_value(), 40);
+
+            let balance_of = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.balance_of(charlie_account));
+            let balance_of_result = client.call_dry_run(&ink_e2e::alice(), &balance_of, 0, None).await;
+            assert_eq!(balance_of_result.return_value(), 60);
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "mocks/psp22_receiver_mock/Cargo.toml")]
+        async fn e2e_transfer_to_accepting_receiver_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let constructor = Psp22InkRef::new(1000);
+            let contract_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let constructor = Psp22ReceiverMockRef::new(None);
+            let receiver_account_id = client
+                .instantiate("psp22_receiver_mock", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let transfer = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.transfer(receiver_account_id, 10, vec![1, 2, 3]));
+            let transfer_result = client
+                .call(&ink_e2e::alice(), transfer, 0, None)
+                .await
+                .expect("transfer failed");
+            let events = contract_events(&transfer_result, contract_account_id.clone());
+            assert_eq!(events.len(), 1);
+            assert!(matches!(
+                &events[0],
+                Event::Transfer(Transfer { from, to, value: 10 }) if *from == Some(alice_account) && *to == Some(receiver_account_id)
+            ));
+            assert_eq!(transfer_result.return_value(), Ok(()));
+
+            let balance_of = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.balance_of(receiver_account_id));
+            let balance_of_result = client.call_dry_run(&ink_e2e::alice(), &balance_of, 0, None).await;
+            assert_eq!(balance_of_result.return_value(), 10);
+
+            let balance_of = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.balance_of(alice_account));
+            let balance_of_result = client.call_dry_run(&ink_e2e::alice(), &balance_of, 0, None).await;
+            assert_eq!(balance_of_result.return_value(), 990);
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "mocks/psp22_receiver_mock/Cargo.toml")]
+        async fn e2e_transfer_to_rejecting_receiver_fails(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let constructor = Psp22InkRef::new(1000);
+            let contract_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let constructor = Psp22ReceiverMockRef::new(Some("Tokens not accepted".to_string()));
+            let receiver_account_id = client
+                .instantiate("psp22_receiver_mock", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let transfer = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.transfer(receiver_account_id, 10, Vec::new()));
+            let transfer_result = client.call_dry_run(&ink_e2e::alice(), &transfer, 0, None).await;
+            assert_eq!(
+                transfer_result.return_value(),
+                Err(PSP22Error::SafeTransferCheckFailed("Tokens not accepted".to_string()))
+            );
+
+            //The rejected transfer is reverted.
+            let transfer = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.transfer(receiver_account_id, 10, Vec::new()));
+            assert!(client.call(&ink_e2e::alice(), transfer, 0, None).await.is_err());
+            let balance_of = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.balance_of(receiver_account_id));
+            let balance_of_result = client.call_dry_run(&ink_e2e::alice(), &balance_of, 0, None).await;
+            assert_eq!(balance_of_result.return_value(), 0);
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn e2e_transfer_to_contract_without_receiver_fails(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let constructor = Psp22InkRef::new(1000);
+            let contract_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            //A second token contract does not implement `PSP22Receiver`.
+            let constructor = Psp22InkRef::new(0);
+            let recipient_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let transfer = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.transfer(recipient_account_id, 10, Vec::new()));
+            let transfer_result = client.call_dry_run(&ink_e2e::alice(), &transfer, 0, None).await;
+            assert!(matches!(
+                transfer_result.return_value(),
+                Err(PSP22Error::SafeTransferCheckFailed(_))
+            ));
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "mocks/flash_borrower_mock/Cargo.toml")]
+        async fn e2e_flash_loan_repaid_by_borrower_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let constructor = Psp22InkRef::new(1000);
+            let contract_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let constructor = FlashBorrowerMockRef::new(Mode::Repay);
+            let borrower_account_id = client
+                .instantiate("flash_borrower_mock", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            //The borrower needs tokens of its own to pay the fee.
+            let mint = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.mint(borrower_account_id, 100));
+            client.call(&ink_e2e::alice(), mint, 0, None).await.expect("mint failed");
+
+            let flash_loan = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.flash_loan(borrower_account_id, 10_000, Vec::new()));
+            let flash_loan_result = client
+                .call(&ink_e2e::alice(), flash_loan, 0, None)
+                .await
+                .expect("flash loan failed");
+            assert_eq!(flash_loan_result.return_value(), Ok(()));
+
+            //The loan is burned back together with the fee of 9.
+            let balance_of = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.balance_of(borrower_account_id));
+            let balance_of_result = client.call_dry_run(&ink_e2e::alice(), &balance_of, 0, None).await;
+            assert_eq!(balance_of_result.return_value(), 91);
+
+            let total_supply = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.total_supply());
+            let total_supply_result = client.call_dry_run(&ink_e2e::alice(), &total_supply, 0, None).await;
+            assert_eq!(total_supply_result.return_value(), 1091);
+
+            let allowance = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.allowance(borrower_account_id, contract_account_id));
+            let allowance_result = client.call_dry_run(&ink_e2e::alice(), &allowance, 0, None).await;
+            assert_eq!(allowance_result.return_value(), 0);
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "mocks/flash_borrower_mock/Cargo.toml")]
+        async fn e2e_flash_loan_moved_by_borrower_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let constructor = Psp22InkRef::new(1000);
+            let contract_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+            let constructor = FlashBorrowerMockRef::new(Mode::Relay(bob_account));
+            let borrower_account_id = client
+                .instantiate("flash_borrower_mock", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let mint = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.mint(borrower_account_id, 100));
+            client.call(&ink_e2e::alice(), mint, 0, None).await.expect("mint failed");
+            //Bob lets the borrower take the loan back from him.
+            let approve = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.approve(borrower_account_id, 10_000));
+            client.call(&ink_e2e::bob(), approve, 0, None).await.expect("approve failed");
+
+            let flash_loan = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.flash_loan(borrower_account_id, 10_000, Vec::new()));
+            let flash_loan_result = client
+                .call(&ink_e2e::alice(), flash_loan, 0, None)
+                .await
+                .expect("flash loan failed");
+            assert_eq!(flash_loan_result.return_value(), Ok(()));
+
+            let balance_of = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.balance_of(borrower_account_id));
+            let balance_of_result = client.call_dry_run(&ink_e2e::alice(), &balance_of, 0, None).await;
+            assert_eq!(balance_of_result.return_value(), 91);
+
+            let balance_of = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.balance_of(bob_account));
+            let balance_of_result = client.call_dry_run(&ink_e2e::alice(), &balance_of, 0, None).await;
+            assert_eq!(balance_of_result.return_value(), 0);
+
+            let allowance = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.allowance(bob_account, borrower_account_id));
+            let allowance_result = client.call_dry_run(&ink_e2e::alice(), &allowance, 0, None).await;
+            assert_eq!(allowance_result.return_value(), 0);
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "mocks/flash_borrower_mock/Cargo.toml")]
+        async fn e2e_flash_loan_under_approved_by_borrower_fails(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let constructor = Psp22InkRef::new(1000);
+            let contract_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let constructor = FlashBorrowerMockRef::new(Mode::UnderApprove);
+            let borrower_account_id = client
+                .instantiate("flash_borrower_mock", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let mint = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.mint(borrower_account_id, 100));
+            client.call(&ink_e2e::alice(), mint, 0, None).await.expect("mint failed");
+
+            let flash_loan = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.flash_loan(borrower_account_id, 10_000, Vec::new()));
+            let flash_loan_result = client.call_dry_run(&ink_e2e::alice(), &flash_loan, 0, None).await;
+            assert_eq!(flash_loan_result.return_value(), Err(PSP22Error::FlashLoanRepaymentFailed));
+
+            //The failed loan is reverted.
+            let flash_loan = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.flash_loan(borrower_account_id, 10_000, Vec::new()));
+            assert!(client.call(&ink_e2e::alice(), flash_loan, 0, None).await.is_err());
+            let balance_of = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.balance_of(borrower_account_id));
+            let balance_of_result = client.call_dry_run(&ink_e2e::alice(), &balance_of, 0, None).await;
+            assert_eq!(balance_of_result.return_value(), 100);
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "mocks/flash_borrower_mock/Cargo.toml")]
+        async fn e2e_flash_loan_repaid_with_locked_tokens_fails(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let constructor = Psp22InkRef::new(1000);
+            let contract_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let constructor = FlashBorrowerMockRef::new(Mode::Repay);
+            let borrower_account_id = client
+                .instantiate("flash_borrower_mock", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            //The tokens that would pay the fee stay locked for a day; the dev node stamps blocks with the wall clock.
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("system time is before the Unix epoch")
+                .as_millis() as Timestamp;
+            let transfer_locked = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.transfer_locked(borrower_account_id, 100, now + 24 * 60 * 60 * 1000));
+            client
+                .call(&ink_e2e::alice(), transfer_locked, 0, None)
+                .await
+                .expect("transfer_locked failed");
+
+            let flash_loan = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.flash_loan(borrower_account_id, 10_000, Vec::new()));
+            let flash_loan_result = client.call_dry_run(&ink_e2e::alice(), &flash_loan, 0, None).await;
+            assert_eq!(flash_loan_result.return_value(), Err(PSP22Error::BalanceLocked));
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "mocks/flash_borrower_mock/Cargo.toml")]
+        async fn e2e_flash_loan_rejected_by_borrower_fails(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let constructor = Psp22InkRef::new(1000);
+            let contract_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let constructor = FlashBorrowerMockRef::new(Mode::Reject);
+            let borrower_account_id = client
+                .instantiate("flash_borrower_mock", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let flash_loan = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.flash_loan(borrower_account_id, 10_000, Vec::new()));
+            let flash_loan_result = client.call_dry_run(&ink_e2e::alice(), &flash_loan, 0, None).await;
+            assert_eq!(
+                flash_loan_result.return_value(),
+                Err(PSP22Error::FlashLoanRejected("Flash loan not wanted".to_string()))
+            );
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "mocks/flash_borrower_mock/Cargo.toml")]
+        async fn e2e_flash_loan_reentry_by_borrower_is_rejected(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let constructor = Psp22InkRef::new(1000);
+            let contract_account_id = client
+                .instantiate("psp22_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let constructor = FlashBorrowerMockRef::new(Mode::Reenter);
+            let borrower_account_id = client
+                .instantiate("flash_borrower_mock", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+            let mint = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.mint(borrower_account_id, 100));
+            client.call(&ink_e2e::alice(), mint, 0, None).await.expect("mint failed");
+
+            let flash_loan = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.flash_loan(borrower_account_id, 10_000, Vec::new()));
+            let flash_loan_result = client
+                .call(&ink_e2e::alice(), flash_loan, 0, None)
+                .await
+                .expect("flash loan failed");
+            assert_eq!(flash_loan_result.return_value(), Ok(()));
+
+            //The nested flash loan attempted from inside the callback was rejected, the approval went through.
+            let reentered = build_message::<FlashBorrowerMockRef>(borrower_account_id.clone())
+                .call(|borrower| borrower.reentered());
+            let reentered_result = client.call_dry_run(&ink_e2e::alice(), &reentered, 0, None).await;
+            assert!(!reentered_result.return_value());
+
+            let balance_of = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.balance_of(borrower_account_id));
+            let balance_of_result = client.call_dry_run(&ink_e2e::alice(), &balance_of, 0, None).await;
+            assert_eq!(balance_of_result.return_value(), 91);
+
+            let total_supply = build_message::<Psp22InkRef>(contract_account_id.clone())
+                .call(|psp22| psp22.total_supply());
+            let total_supply_result = client.call_dry_run(&ink_e2e::alice(), &total_supply, 0, None).await;
+            assert_eq!(total_supply_result.return_value(), 1091);
             Ok(())
         }
     }
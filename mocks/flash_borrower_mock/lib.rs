@@ -0,0 +1,166 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+pub use self::flash_borrower_mock::{FlashBorrowerMock, FlashBorrowerMockRef, Mode};
+
+/// Flash loan borrower with a configurable behaviour, used by the e2e tests of `psp22_ink`.
+#[ink::contract]
+mod flash_borrower_mock {
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::env::CallFlags;
+    use ink::prelude::string::{String, ToString};
+    use ink::prelude::vec::Vec;
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum FlashBorrowerError {
+        FlashloanRejected(String),
+    }
+
+    //The borrower also accepts tokens taken back in `Relay` mode, so it implements `PSP22Receiver` as well.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PSP22ReceiverError {
+        TransferRejected(String),
+    }
+
+    #[ink::trait_definition]
+    pub trait PSP22Receiver{
+        #[ink(message)]
+        fn before_received(&mut self, operator: AccountId, from: AccountId, value: Balance, data: Vec<u8>) -> core::result::Result<(), PSP22ReceiverError>;
+    }
+
+    #[ink::trait_definition]
+    pub trait FlashBorrower{
+        #[ink(message)]
+        fn on_flash_loan(&mut self, initiator: AccountId, token: AccountId, amount: Balance, fee: Balance, data: Vec<u8>) -> core::result::Result<(), FlashBorrowerError>;
+    }
+
+    /// What the borrower does when it receives a flash loan.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum Mode {
+        /// Approves the loan and the fee to be taken back.
+        Repay,
+        /// Approves only the loan, not the fee, to be taken back.
+        UnderApprove,
+        /// Rejects the loan.
+        Reject,
+        /// Approves the loan and the fee, then tries to take a nested flash loan from the lending contract.
+        Reenter,
+        /// Transfers the loan to the given account and takes it back with an allowance that account gave
+        /// beforehand, then approves the loan and the fee.
+        Relay(AccountId),
+    }
+
+    #[ink(storage)]
+    pub struct FlashBorrowerMock {
+        mode: Mode,
+
+        reentered: bool,
+    }
+
+    impl FlashBorrowerMock {
+        #[ink(constructor)]
+        pub fn new(mode: Mode) -> Self {
+            Self { mode, reentered: false }
+        }
+
+        /// Returns whether the nested flash loan attempted in `Reenter` mode succeeded.
+        #[ink(message)]
+        pub fn reentered(&self) -> bool {
+            self.reentered
+        }
+
+        //Returns whether `PSP22::approve` on `token` succeeded. Only success matters, so the error is not decoded.
+        fn approve(&self, token: AccountId, value: Balance) -> bool {
+            let call_result = build_call::<ink::env::DefaultEnvironment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::approve")))
+                        .push_arg(token)
+                        .push_arg(value),
+                )
+                .returns::<core::result::Result<(), ()>>()
+                .try_invoke();
+            matches!(call_result, Ok(Ok(Ok(()))))
+        }
+
+        //Returns whether a nested `PSP22FlashLender::flash_loan` on `token` succeeded.
+        fn flash_loan(&self, token: AccountId, value: Balance) -> bool {
+            let call_result = build_call::<ink::env::DefaultEnvironment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22FlashLender::flash_loan")))
+                        .push_arg(self.env().account_id())
+                        .push_arg(value)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), ()>>()
+                .try_invoke();
+            matches!(call_result, Ok(Ok(Ok(()))))
+        }
+
+        //Returns whether `PSP22::transfer` of `value` tokens to `to` succeeded.
+        fn transfer(&self, token: AccountId, to: AccountId, value: Balance) -> bool {
+            let call_result = build_call::<ink::env::DefaultEnvironment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer")))
+                        .push_arg(to)
+                        .push_arg(value)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), ()>>()
+                .try_invoke();
+            matches!(call_result, Ok(Ok(Ok(()))))
+        }
+
+        //Returns whether `PSP22::transfer_from` of `value` tokens from `from` to this contract succeeded.
+        //The token calls `PSP22Receiver::before_received` back on this contract, so re-entry is allowed.
+        fn transfer_from(&self, token: AccountId, from: AccountId, value: Balance) -> bool {
+            let call_result = build_call::<ink::env::DefaultEnvironment>()
+                .call(token)
+                .call_flags(CallFlags::default().set_allow_reentry(true))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer_from")))
+                        .push_arg(from)
+                        .push_arg(self.env().account_id())
+                        .push_arg(value)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), ()>>()
+                .try_invoke();
+            matches!(call_result, Ok(Ok(Ok(()))))
+        }
+    }
+
+    impl FlashBorrower for FlashBorrowerMock{
+        #[ink(message)]
+        fn on_flash_loan(&mut self, _initiator: AccountId, token: AccountId, amount: Balance, fee: Balance, _data: Vec<u8>) -> core::result::Result<(), FlashBorrowerError> {
+            if let Mode::Relay(account) = self.mode {
+                if !self.transfer(token, account, amount) || !self.transfer_from(token, account, amount) {
+                    return Err(FlashBorrowerError::FlashloanRejected("Relay failed".to_string()));
+                }
+            }
+            let approved = match self.mode {
+                Mode::Repay | Mode::Reenter | Mode::Relay(_) => self.approve(token, amount + fee),
+                Mode::UnderApprove => self.approve(token, amount),
+                Mode::Reject => return Err(FlashBorrowerError::FlashloanRejected("Flash loan not wanted".to_string())),
+            };
+            if !approved {
+                return Err(FlashBorrowerError::FlashloanRejected("Approval failed".to_string()));
+            }
+            if self.mode == Mode::Reenter {
+                self.reentered = self.flash_loan(token, 1);
+            }
+            Ok(())
+        }
+    }
+
+    impl PSP22Receiver for FlashBorrowerMock{
+        #[ink(message)]
+        fn before_received(&mut self, _operator: AccountId, _from: AccountId, _value: Balance, _data: Vec<u8>) -> core::result::Result<(), PSP22ReceiverError> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,46 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+pub use self::psp22_receiver_mock::{Psp22ReceiverMock, Psp22ReceiverMockRef};
+
+/// Contract that accepts or rejects incoming PSP22 transfers, used by the e2e tests of `psp22_ink`.
+#[ink::contract]
+mod psp22_receiver_mock {
+    use ink::prelude::string::String;
+    use ink::prelude::vec::Vec;
+
+    //Mirrors `psp22_ink::PSP22Receiver`, which cannot be imported because `psp22_ink` tests against this mock.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PSP22ReceiverError {
+        TransferRejected(String),
+    }
+
+    #[ink::trait_definition]
+    pub trait PSP22Receiver{
+        #[ink(message)]
+        fn before_received(&mut self, operator: AccountId, from: AccountId, value: Balance, data: Vec<u8>) -> core::result::Result<(), PSP22ReceiverError>;
+    }
+
+    #[ink(storage)]
+    pub struct Psp22ReceiverMock {
+        reject_reason: Option<String>,
+    }
+
+    impl Psp22ReceiverMock {
+        /// Creates a receiver that rejects every transfer with `reject_reason` if given, and accepts them otherwise.
+        #[ink(constructor)]
+        pub fn new(reject_reason: Option<String>) -> Self {
+            Self { reject_reason }
+        }
+    }
+
+    impl PSP22Receiver for Psp22ReceiverMock{
+        #[ink(message)]
+        fn before_received(&mut self, _operator: AccountId, _from: AccountId, _value: Balance, _data: Vec<u8>) -> core::result::Result<(), PSP22ReceiverError> {
+            match &self.reject_reason {
+                Some(reason) => Err(PSP22ReceiverError::TransferRejected(reason.clone())),
+                None => Ok(()),
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::errors::{PSP22Error, PSP22ReceiverError};
+use ink::env::call::{build_call, ExecutionInput, Selector};
+use ink::env::DefaultEnvironment;
+use ink::prelude::{string::ToString, vec::Vec};
+use ink::primitives::AccountId;
+
+/// Calls `PSP22Receiver::before_received` on `to` if it is a contract, failing if the callee rejects the
+/// transfer or does not implement the trait.
+///
+/// Call it once the balances have been updated, so that the callee sees them and a rejection reverts the
+/// whole message. The call does not allow re-entry, so the callee cannot call back into the token.
+pub fn check_receiver(
+    operator: AccountId,
+    from: AccountId,
+    to: AccountId,
+    value: u128,
+    data: Vec<u8>,
+) -> Result<(), PSP22Error> {
+    if !ink::env::is_contract::<DefaultEnvironment>(&to) {
+        return Ok(());
+    }
+    let call_result = build_call::<DefaultEnvironment>()
+        .call(to)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22Receiver::before_received")))
+                .push_arg(operator)
+                .push_arg(from)
+                .push_arg(value)
+                .push_arg(data),
+        )
+        .returns::<Result<(), PSP22ReceiverError>>()
+        .try_invoke();
+    receiver_check_result(call_result)
+}
+
+//Maps the outcome of the `PSP22Receiver::before_received` call to the result of the transfer.
+pub(crate) fn receiver_check_result(
+    call_result: ink::env::Result<ink::MessageResult<Result<(), PSP22ReceiverError>>>,
+) -> Result<(), PSP22Error> {
+    match call_result {
+        Ok(Ok(Ok(()))) => Ok(()),
+        Ok(Ok(Err(PSP22ReceiverError::TransferRejected(reason)))) => Err(PSP22Error::SafeTransferCheckFailed(reason)),
+        _ => Err(PSP22Error::SafeTransferCheckFailed(
+            "Recipient is a contract and does not implement PSP22Receiver".to_string(),
+        )),
+    }
+}